@@ -25,13 +25,13 @@ const JSON: Json = const_json!({
 
 fn main() {
     assert_eq!((), JSON["null"].null());
-    assert_eq!(true, JSON["bool"].bool());
+    assert!(JSON["bool"].bool());
     assert_eq!(12.3, JSON["float"].float());
     assert_eq!(42, JSON["int"].int());
     assert_eq!("Hello, World!", JSON["str"].str());
     assert_eq!(1, JSON["array"][0].int());
     assert_eq!((), JSON["array"][1].null());
-    assert_eq!(false, JSON["object"]["inner_bool"].bool());
+    assert!(!JSON["object"]["inner_bool"].bool());
     assert_eq!("foo bar", JSON["object"]["inner_str"].str());
     assert_eq!("string", JSON["rust"].str());
     assert_eq!(22, JSON["function_result"].int());
@@ -41,11 +41,15 @@ fn main() {
 
     let mut arr = [0; FUNCTION_RESULT as usize];
 
-    for i in 0..arr.len() {
-        arr[i] = i;
+    for (i, elem) in arr.iter_mut().enumerate() {
+        *elem = i;
     }
 
     assert_eq!(arr.as_slice(), (0..22).collect::<Vec<_>>().as_slice());
 
+    // A hash of the whole value can be computed at compile time too
+    const HASH: u64 = JSON.fnv_hash();
+    assert_eq!(HASH, 0xc47a_8a3d_d238_5948);
+
     println!("{JSON:#?}");
 }
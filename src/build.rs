@@ -0,0 +1,153 @@
+//! Building owned values imperatively.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::OwnedJson;
+
+/// Adds entries to the object being built by [`OwnedJson::build_object`].
+#[derive(Debug, Default)]
+pub struct ObjectBuilder(Vec<(String, OwnedJson)>);
+
+impl ObjectBuilder {
+    /// Adds an entry at the end of the object. A key that is already present is added again, as
+    /// a duplicate, rather than replaced.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<OwnedJson>) -> &mut Self {
+        self.0.push((key.into(), value.into()));
+        self
+    }
+
+    /// Makes room for at least `additional` more entries without reallocating.
+    pub fn reserve(&mut self, additional: usize) -> &mut Self {
+        self.0.reserve(additional);
+        self
+    }
+}
+
+/// Adds elements to the array being built by [`OwnedJson::build_array`].
+#[derive(Debug, Default)]
+pub struct ArrayBuilder(Vec<OwnedJson>);
+
+impl ArrayBuilder {
+    /// Adds an element at the end of the array.
+    pub fn push(&mut self, value: impl Into<OwnedJson>) -> &mut Self {
+        self.0.push(value.into());
+        self
+    }
+
+    /// Makes room for at least `additional` more elements without reallocating.
+    pub fn reserve(&mut self, additional: usize) -> &mut Self {
+        self.0.reserve(additional);
+        self
+    }
+}
+
+impl OwnedJson {
+    /// Builds an object by passing a builder to `build`, which adds the entries in order.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let name = String::from("example");
+    /// let json = OwnedJson::build_object(|o| {
+    ///     o.insert("name", name);
+    ///     o.insert("port", 8080);
+    ///     o.insert("tags", OwnedJson::build_array(|a| {
+    ///         a.push("fast").push(true).push(1.5);
+    ///     }));
+    /// });
+    /// assert_eq!(json, const_json!({"name": "example", "port": 8080, "tags": ["fast", true, 1.5]}));
+    /// ```
+    pub fn build_object(build: impl FnOnce(&mut ObjectBuilder)) -> Self {
+        let mut builder = ObjectBuilder::default();
+        build(&mut builder);
+        Self::Object(builder.0)
+    }
+
+    /// Builds an array by passing a builder to `build`, which adds the elements in order.
+    pub fn build_array(build: impl FnOnce(&mut ArrayBuilder)) -> Self {
+        let mut builder = ArrayBuilder::default();
+        build(&mut builder);
+        Self::Array(builder.0)
+    }
+
+    /// Creates an empty array with room for at least `capacity` elements.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut squares = OwnedJson::array_with_capacity(100);
+    /// for i in 0..100_i64 {
+    ///     squares.push(OwnedJson::from(i * i)).unwrap();
+    /// }
+    /// assert_eq!(squares.get_idx(99), &const_json!(9801));
+    /// ```
+    pub fn array_with_capacity(capacity: usize) -> Self {
+        Self::Array(Vec::with_capacity(capacity))
+    }
+
+    /// Creates an empty object with room for at least `capacity` entries.
+    pub fn object_with_capacity(capacity: usize) -> Self {
+        Self::Object(Vec::with_capacity(capacity))
+    }
+
+    /// Makes room for at least `additional` more elements of an array or entries of an object
+    /// without reallocating. Does nothing if `self` is neither.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::object_with_capacity(1);
+    /// json.reserve(2);
+    /// let entries = [("a".into(), OwnedJson::Int(1)), ("b".into(), OwnedJson::Int(2))];
+    /// json.extend_object(entries).unwrap();
+    /// assert_eq!(json, const_json!({"a": 1, "b": 2}));
+    ///
+    /// let mut scalar = OwnedJson::Int(1);
+    /// scalar.reserve(10);
+    /// assert_eq!(scalar, const_json!(1));
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        match self {
+            Self::Array(arr) => arr.reserve(additional),
+            Self::Object(obj) => obj.reserve(additional),
+            _ => {}
+        }
+    }
+}
+
+impl From<()> for OwnedJson {
+    fn from(value: ()) -> Self {
+        Self::Null(value)
+    }
+}
+
+impl From<bool> for OwnedJson {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for OwnedJson {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<i64> for OwnedJson {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<&str> for OwnedJson {
+    fn from(value: &str) -> Self {
+        Self::Str(value.into())
+    }
+}
+
+impl From<String> for OwnedJson {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
@@ -0,0 +1,119 @@
+//! Converting values into plain Rust types.
+
+use crate::{Json, JsonError};
+
+impl<'a> Json<'a> {
+    /// Converts an array of exactly `N` elements into a Rust array, converting each element with
+    /// its [`TryFrom`] impl.
+    ///
+    /// Returns `None` if `self` is not an array, does not have exactly `N` elements, or has an
+    /// element that fails to convert. Each element can also be converted on its own, to see why it
+    /// fails.
+    ///
+    /// ```rust
+    /// use const_json::{Json, JsonError, const_json};
+    ///
+    /// const THEME: Json = const_json!({
+    ///     "color": [255, 128, 0],
+    ///     "bad": [255, 300, 0],
+    ///     "fonts": ["serif", "mono"]
+    /// });
+    ///
+    /// assert_eq!(THEME["color"].as_array_of::<3, u8>(), Some([255, 128, 0]));
+    /// assert_eq!(THEME["color"].as_array_of::<4, u8>(), None);
+    /// assert_eq!(THEME["bad"].as_array_of::<3, u8>(), None);
+    /// assert_eq!(THEME["bad"].as_array_of::<3, u16>(), Some([255, 300, 0]));
+    /// assert_eq!(THEME["fonts"].as_array_of::<2, &str>(), Some(["serif", "mono"]));
+    /// assert_eq!(THEME.as_array_of::<0, bool>(), None);
+    ///
+    /// let err = u8::try_from(&THEME["bad"][1]).unwrap_err();
+    /// assert_eq!(err, JsonError::OutOfRange { expected: "u8" });
+    /// assert_eq!(err.to_string(), "int out of range for u8");
+    /// ```
+    pub fn as_array_of<const N: usize, T>(&self) -> Option<[T; N]>
+    where
+        T: for<'j> TryFrom<&'j Json<'a>>,
+    {
+        let Self::Array(arr) = *self else {
+            return None;
+        };
+        if arr.len() != N {
+            return None;
+        }
+        let elems: [Option<T>; N] = core::array::from_fn(|idx| T::try_from(&arr[idx]).ok());
+        if elems.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(elems.map(|elem| elem.expect("checked above")))
+    }
+}
+
+impl TryFrom<&Json<'_>> for bool {
+    type Error = JsonError<'static>;
+
+    fn try_from(json: &Json<'_>) -> Result<Self, Self::Error> {
+        match *json {
+            Json::Bool(b) => Ok(b),
+            _ => Err(JsonError::WrongType {
+                expected: "bool",
+                found: json.type_name(),
+            }),
+        }
+    }
+}
+
+/// Both floats and integers convert to `f64`, the integers rounding to the nearest `f64` if
+/// they are too large to be exact.
+impl TryFrom<&Json<'_>> for f64 {
+    type Error = JsonError<'static>;
+
+    fn try_from(json: &Json<'_>) -> Result<Self, Self::Error> {
+        match *json {
+            Json::Float(f) => Ok(f),
+            Json::Int(i) => Ok(i as f64),
+            _ => Err(JsonError::WrongType {
+                expected: "float",
+                found: json.type_name(),
+            }),
+        }
+    }
+}
+
+impl<'a> TryFrom<&Json<'a>> for &'a str {
+    type Error = JsonError<'static>;
+
+    fn try_from(json: &Json<'a>) -> Result<Self, Self::Error> {
+        match *json {
+            Json::Str(s) => Ok(s),
+            _ => Err(JsonError::WrongType {
+                expected: "string",
+                found: json.type_name(),
+            }),
+        }
+    }
+}
+
+/// Implements [`TryFrom<&Json>`] for integer types, accepting a [`Json::Int`] that fits in range.
+macro_rules! impl_try_from_int {
+    ($($int:ident)*) => {
+        $(
+            impl TryFrom<&Json<'_>> for $int {
+                type Error = JsonError<'static>;
+
+                fn try_from(json: &Json<'_>) -> Result<Self, Self::Error> {
+                    match *json {
+                        Json::Int(i) => i.try_into().map_err(|_| JsonError::OutOfRange {
+                            expected: stringify!($int),
+                        }),
+                        _ => Err(JsonError::WrongType {
+                            expected: "int",
+                            found: json.type_name(),
+                        }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_int!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
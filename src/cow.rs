@@ -0,0 +1,246 @@
+//! A JSON value that borrows what it can and owns only what has changed.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::{Json, OwnedJson};
+
+/// A JSON value that borrows as much as it can, and owns only the parts that have been changed
+/// or could not be borrowed, such as strings that [`parse_borrowed`](crate::parse_borrowed) had to
+/// unescape.
+///
+/// Converting a [`Json`] gives a [`Borrowed`](Self::Borrowed) value without copying anything.
+/// Reaching into it with [`get_mut`](Self::get_mut) or [`get_idx_mut`](Self::get_idx_mut) expands
+/// only the containers along the way, one level at a time, leaving their other children borrowed
+/// whole. So overriding one field of a large compile-time default allocates a vector for each
+/// container above that field, and nothing else.
+///
+/// ```rust
+/// use const_json::{CowJson, Json, const_json};
+///
+/// const DEFAULTS: Json = const_json!({
+///     "server": {"host": "localhost", "port": 8080},
+///     "features": ["a", "b"],
+///     "limits": {"cpu": 2}
+/// });
+///
+/// let mut config = CowJson::from(DEFAULTS);
+/// *config.get_mut("server").unwrap().get_mut("port").unwrap() = CowJson::Int(9000);
+///
+/// // `features` and `limits` are still borrowed from `DEFAULTS`
+/// assert!(matches!(config.get_mut("features"), Some(CowJson::Borrowed(_))));
+/// assert_eq!(config, const_json!({
+///     "server": {"host": "localhost", "port": 9000},
+///     "features": ["a", "b"],
+///     "limits": {"cpu": 2}
+/// }));
+/// assert_eq!(config.into_owned(), const_json!({
+///     "server": {"host": "localhost", "port": 9000},
+///     "features": ["a", "b"],
+///     "limits": {"cpu": 2}
+/// }));
+/// ```
+///
+/// Two values are equal if they hold the same JSON, however much of each is borrowed.
+#[derive(Clone, Debug)]
+pub enum CowJson<'a> {
+    /// `null`
+    Null(()),
+    /// `true` or `false`
+    Bool(bool),
+    /// A floating point number
+    Float(f64),
+    /// A 64-bit signed integer
+    Int(i64),
+    /// A string
+    Str(Cow<'a, str>),
+    /// An untyped Json array
+    Array(Vec<CowJson<'a>>),
+    /// A Json key-value map
+    Object(Vec<(Cow<'a, str>, CowJson<'a>)>),
+    /// A whole value borrowed as it is, children and all.
+    Borrowed(Json<'a>),
+}
+
+impl<'a> CowJson<'a> {
+    /// Gets the value stored at the given key of an object mutably, expanding `self` first if it
+    /// is [`Borrowed`](Self::Borrowed). If the key appears more than once, the first entry is
+    /// used.
+    ///
+    /// Returns `None` if `self` is not an object or the key could not be found.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Self> {
+        self.expand();
+        match self {
+            Self::Object(obj) => obj.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Gets the element at the given index of an array mutably, expanding `self` first if it is
+    /// [`Borrowed`](Self::Borrowed).
+    ///
+    /// Returns `None` if `self` is not an array or the index is out of range.
+    ///
+    /// ```rust
+    /// use const_json::{CowJson, Json, const_json};
+    ///
+    /// const LIST: Json = const_json!([{"id": 1}, {"id": 2}]);
+    ///
+    /// let mut list = CowJson::from(LIST);
+    /// *list.get_idx_mut(1).unwrap().get_mut("id").unwrap() = CowJson::Int(3);
+    /// assert_eq!(list, const_json!([{"id": 1}, {"id": 3}]));
+    /// assert!(list.get_idx_mut(2).is_none());
+    /// ```
+    pub fn get_idx_mut(&mut self, index: usize) -> Option<&mut Self> {
+        self.expand();
+        match self {
+            Self::Array(arr) => arr.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Converts `self` into an [`OwnedJson`], copying everything that is borrowed.
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use const_json::{CowJson, OwnedJson, const_json};
+    ///
+    /// let owned = OwnedJson::from(const_json!({"name": "app", "tags": ["a"]}));
+    ///
+    /// // Borrowing the strings of an `OwnedJson`, or taking them over
+    /// let borrowed = CowJson::from(&owned);
+    /// assert!(matches!(&borrowed, CowJson::Object(obj) if matches!(obj[0].0, Cow::Borrowed(_))));
+    /// assert_eq!(borrowed.into_owned(), owned);
+    /// assert_eq!(OwnedJson::from(CowJson::from(owned.clone())), owned);
+    /// ```
+    pub fn into_owned(self) -> OwnedJson {
+        match self {
+            Self::Null(()) => OwnedJson::Null(()),
+            Self::Bool(b) => OwnedJson::Bool(b),
+            Self::Float(f) => OwnedJson::Float(f),
+            Self::Int(i) => OwnedJson::Int(i),
+            Self::Str(s) => OwnedJson::Str(s.into_owned()),
+            Self::Array(arr) => OwnedJson::Array(arr.into_iter().map(Self::into_owned).collect()),
+            Self::Object(obj) => OwnedJson::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+            Self::Borrowed(json) => json.into(),
+        }
+    }
+
+    /// Replaces a [`Borrowed`](Self::Borrowed) value with the matching variant, whose children,
+    /// if any, are each borrowed whole.
+    fn expand(&mut self) {
+        let Self::Borrowed(json) = *self else {
+            return;
+        };
+        *self = match json {
+            Json::Null(()) => Self::Null(()),
+            Json::Bool(b) => Self::Bool(b),
+            Json::Float(f) => Self::Float(f),
+            Json::Int(i) => Self::Int(i),
+            Json::Str(s) => Self::Str(Cow::Borrowed(s)),
+            Json::Array(arr) => Self::Array(arr.iter().map(|elem| Self::Borrowed(*elem)).collect()),
+            Json::Object(obj) => Self::Object(
+                obj.iter()
+                    .map(|(key, value)| (Cow::Borrowed(*key), Self::Borrowed(*value)))
+                    .collect(),
+            ),
+        };
+    }
+}
+
+impl<'a> From<Json<'a>> for CowJson<'a> {
+    fn from(json: Json<'a>) -> Self {
+        Self::Borrowed(json)
+    }
+}
+
+impl<'a> From<&'a OwnedJson> for CowJson<'a> {
+    fn from(json: &'a OwnedJson) -> Self {
+        match json {
+            OwnedJson::Null(()) => Self::Null(()),
+            OwnedJson::Bool(b) => Self::Bool(*b),
+            OwnedJson::Float(f) => Self::Float(*f),
+            OwnedJson::Int(i) => Self::Int(*i),
+            OwnedJson::Str(s) => Self::Str(Cow::Borrowed(s)),
+            OwnedJson::Array(arr) => Self::Array(arr.iter().map(Self::from).collect()),
+            OwnedJson::Object(obj) => Self::Object(
+                obj.iter()
+                    .map(|(key, value)| (Cow::Borrowed(key.as_str()), Self::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<OwnedJson> for CowJson<'_> {
+    fn from(json: OwnedJson) -> Self {
+        match json {
+            OwnedJson::Null(()) => Self::Null(()),
+            OwnedJson::Bool(b) => Self::Bool(b),
+            OwnedJson::Float(f) => Self::Float(f),
+            OwnedJson::Int(i) => Self::Int(i),
+            OwnedJson::Str(s) => Self::Str(Cow::Owned(s)),
+            OwnedJson::Array(arr) => Self::Array(arr.into_iter().map(Self::from).collect()),
+            OwnedJson::Object(obj) => Self::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (Cow::Owned(key), Self::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<CowJson<'_>> for OwnedJson {
+    fn from(json: CowJson<'_>) -> Self {
+        json.into_owned()
+    }
+}
+
+impl PartialEq for CowJson<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Borrowed(json), other) | (other, Self::Borrowed(json)) => other == json,
+            (Self::Null(()), Self::Null(())) => true,
+            (Self::Bool(l), Self::Bool(r)) => l == r,
+            (Self::Float(l), Self::Float(r)) => l == r,
+            (Self::Int(l), Self::Int(r)) => l == r,
+            (Self::Str(l), Self::Str(r)) => l == r,
+            (Self::Array(l), Self::Array(r)) => l == r,
+            (Self::Object(l), Self::Object(r)) => l == r,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<Json<'_>> for CowJson<'_> {
+    fn eq(&self, other: &Json<'_>) -> bool {
+        match (self, *other) {
+            (Self::Borrowed(json), other) => *json == other,
+            (Self::Null(()), Json::Null(())) => true,
+            (Self::Bool(l), Json::Bool(r)) => *l == r,
+            (Self::Float(l), Json::Float(r)) => *l == r,
+            (Self::Int(l), Json::Int(r)) => *l == r,
+            (Self::Str(l), Json::Str(r)) => l == r,
+            (Self::Array(l), Json::Array(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l == r)
+            }
+            (Self::Object(l), Json::Object(r)) => {
+                l.len() == r.len()
+                    && l.iter()
+                        .zip(r)
+                        .all(|((lk, lv), (rk, rv))| lk == rk && lv == rv)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<CowJson<'_>> for Json<'_> {
+    fn eq(&self, other: &CowJson<'_>) -> bool {
+        other == self
+    }
+}
@@ -0,0 +1,90 @@
+//! Errors from the fallible accessors.
+
+use core::fmt;
+
+use crate::Json;
+#[cfg(feature = "alloc")]
+use crate::OwnedJson;
+
+/// An error from a fallible accessor, such as [`Json::try_index`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum JsonError<'k> {
+    /// The object has no entry with the given key.
+    KeyNotFound {
+        /// The key that was looked up.
+        key: &'k str,
+    },
+    /// The index is not below the length of the array.
+    IndexOutOfRange {
+        /// The index that was asked for.
+        index: usize,
+        /// The length of the array.
+        len: usize,
+    },
+    /// The value is not of the type the operation needs.
+    WrongType {
+        /// The type the operation needs, such as `"array"`.
+        expected: &'static str,
+        /// The type of the value, as named by [`Json::type_name`].
+        found: &'static str,
+    },
+    /// The value is an integer too large or too small for the type it is being converted to.
+    OutOfRange {
+        /// The type being converted to, such as `"u8"`.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for JsonError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeyNotFound { key } => write!(f, "key '{key}' not found"),
+            Self::IndexOutOfRange { index, len } => {
+                write!(f, "index {index} out of range for array of length {len}")
+            }
+            Self::WrongType { expected, found } => write!(f, "expected {expected}, found {found}"),
+            Self::OutOfRange { expected } => write!(f, "int out of range for {expected}"),
+        }
+    }
+}
+
+impl Json<'_> {
+    /// The name of the variant of `self`, as used in [`JsonError::WrongType`]: one of `"null"`,
+    /// `"bool"`, `"float"`, `"int"`, `"string"`, `"array"` and `"object"`.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!({"list": [1.5]});
+    /// assert_eq!(JSON.type_name(), "object");
+    /// assert_eq!(JSON["list"][0].type_name(), "float");
+    /// ```
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null(()) => "null",
+            Self::Bool(_) => "bool",
+            Self::Float(_) => "float",
+            Self::Int(_) => "int",
+            Self::Str(_) => "string",
+            Self::Array(_) => "array",
+            Self::Object(_) => "object",
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedJson {
+    /// The name of the variant of `self`, the same as [`Json::type_name`].
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Null(()) => "null",
+            Self::Bool(_) => "bool",
+            Self::Float(_) => "float",
+            Self::Int(_) => "int",
+            Self::Str(_) => "string",
+            Self::Array(_) => "array",
+            Self::Object(_) => "object",
+        }
+    }
+}
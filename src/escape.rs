@@ -0,0 +1,315 @@
+//! JSON string escaping.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use crate::{ParseError, ParseErrorKind};
+
+/// Writes `s` with every character JSON requires to be escaped replaced by its escape sequence.
+///
+/// If `ascii_only` is set, every non-ASCII character is escaped too, as one `\uXXXX` escape or as a
+/// UTF-16 surrogate pair of them. If `escape_slashes` is set, `/` is escaped as `\/`.
+pub(crate) fn write_escaped<W: fmt::Write>(
+    w: &mut W,
+    s: &str,
+    ascii_only: bool,
+    escape_slashes: bool,
+) -> fmt::Result {
+    if !ascii_only && !escape_slashes && !needs_escaping(s) {
+        return w.write_str(s);
+    }
+    let mut run = 0;
+    for (idx, c) in s.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            '/' if escape_slashes => "\\/",
+            '\0'..='\u{1f}' => "",
+            c if ascii_only && !c.is_ascii() => "",
+            _ => continue,
+        };
+        w.write_str(&s[run..idx])?;
+        if escape.is_empty() {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                write!(w, "\\u{unit:04x}")?;
+            }
+        } else {
+            w.write_str(escape)?;
+        }
+        run = idx + c.len_utf8();
+    }
+    w.write_str(&s[run..])
+}
+
+/// Writes the JSON-escaped form of `s` into the start of `buf`, without surrounding quotes, in a
+/// `const` context. Returns the number of bytes written, or `None` if they do not fit, in which
+/// case the contents of `buf` are unspecified.
+///
+/// The escaping is the same as [`Serializer`](crate::Serializer)'s by default: quotes and
+/// backslashes are escaped with a backslash, control characters with a short escape such as `\n`
+/// where JSON has one and as `\u00XX` otherwise, and everything else is copied through.
+///
+/// ```rust
+/// use const_json::escape_into;
+///
+/// const ESCAPED: ([u8; 32], usize) = {
+///     let mut buf = [0; 32];
+///     let len = escape_into("say \"hi\"\n\u{1}é", &mut buf).unwrap();
+///     (buf, len)
+/// };
+/// let escaped = std::str::from_utf8(&ESCAPED.0[..ESCAPED.1]).unwrap();
+/// assert_eq!(escaped, r#"say \"hi\"\n\u0001é"#);
+///
+/// assert_eq!(escape_into("\"", &mut [0; 1]), None);
+/// ```
+pub const fn escape_into(s: &str, buf: &mut [u8]) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut len = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let (escape, escape_len) = escape_byte(bytes[idx]);
+        if len + escape_len > buf.len() {
+            return None;
+        }
+        let mut e = 0;
+        while e < escape_len {
+            buf[len] = escape[e];
+            len += 1;
+            e += 1;
+        }
+        idx += 1;
+    }
+    Some(len)
+}
+
+/// Computes the length in bytes of the JSON-escaped form of `s`, without surrounding quotes, in a
+/// `const` context. This is exactly what [`escape_into`] writes, so it gives the buffer size
+/// needed.
+///
+/// Quotes, backslashes and the control characters with a short escape (`\b`, `\f`, `\n`, `\r`
+/// and `\t`) take 2 bytes, other control characters take 6, as `\u00XX`, and everything else
+/// keeps its UTF-8 length.
+///
+/// ```rust
+/// use const_json::{escape_into, escaped_len};
+///
+/// const TEXT: &str = "\"quoted\"\nline\u{1f}café 😀";
+/// const LEN: usize = escaped_len(TEXT);
+/// assert_eq!(LEN, 2 + 6 + 2 + 2 + 4 + 6 + 5 + 1 + 4);
+///
+/// let mut buf = [0; LEN];
+/// assert_eq!(escape_into(TEXT, &mut buf), Some(LEN));
+/// # #[cfg(feature = "alloc")]
+/// assert_eq!(LEN, const_json::escape_str(TEXT).len());
+/// ```
+pub const fn escaped_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut len = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        len += escape_byte(bytes[idx]).1;
+        idx += 1;
+    }
+    len
+}
+
+/// Whether any character of `s` has to be escaped in a JSON string: a quote, a backslash or a
+/// control character. If not, `s` can be written between quotes as it is, and it is its own
+/// [`escape_into`] output.
+///
+/// ```rust
+/// use const_json::{escaped_len, needs_escaping};
+///
+/// const PLAIN: bool = needs_escaping("café, 😀 / <b>");
+/// assert!(!PLAIN);
+/// assert!(needs_escaping("say \"hi\""));
+/// assert!(needs_escaping(r"C:\temp"));
+/// assert!(needs_escaping("tab\there"));
+/// assert!(needs_escaping("\u{1f}"));
+/// assert!(!needs_escaping("\u{7f}"));
+///
+/// for s in ["plain", "a\nb", "\""] {
+///     assert_eq!(needs_escaping(s), escaped_len(s) != s.len());
+/// }
+/// ```
+pub const fn needs_escaping(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if escape_byte(bytes[idx]).1 != 1 {
+            return true;
+        }
+        idx += 1;
+    }
+    false
+}
+
+/// The escaped form of a single byte of a string, in the first of the returned number of bytes.
+/// Bytes that need no escaping, including those of non-ASCII characters, come back unchanged.
+pub(crate) const fn escape_byte(b: u8) -> ([u8; 6], usize) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut escape = [b'\\', 0, b'0', b'0', 0, 0];
+    match short_escape(b) {
+        Some(c) => {
+            escape[1] = c;
+            (escape, 2)
+        }
+        None if b < 0x20 => {
+            escape[1] = b'u';
+            escape[4] = HEX[(b >> 4) as usize];
+            escape[5] = HEX[(b & 0xf) as usize];
+            (escape, 6)
+        }
+        None => {
+            escape[0] = b;
+            (escape, 1)
+        }
+    }
+}
+
+/// The letter of the two-character escape for byte `b`, if it has one.
+const fn short_escape(b: u8) -> Option<u8> {
+    match b {
+        b'"' => Some(b'"'),
+        b'\\' => Some(b'\\'),
+        b'\n' => Some(b'n'),
+        b'\r' => Some(b'r'),
+        b'\t' => Some(b't'),
+        0x08 => Some(b'b'),
+        0x0c => Some(b'f'),
+        _ => None,
+    }
+}
+
+/// Applies JSON string escaping to `s`, without adding surrounding quotes.
+///
+/// Quotes, backslashes and control characters are escaped; everything else, including non-ASCII
+/// characters, is copied through unchanged.
+///
+/// ```rust
+/// use const_json::escape_str;
+///
+/// assert_eq!(escape_str("say \"hi\"\n"), r#"say \"hi\"\n"#);
+/// assert_eq!(escape_str("\u{1}é"), r"\u0001é");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn escape_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    // Writing to a `String` cannot fail
+    let _ = write_escaped(&mut out, s, false, false);
+    out
+}
+
+/// Reverses JSON string escaping on `s`, which should not include surrounding quotes.
+///
+/// `\uXXXX` escapes are decoded, with UTF-16 surrogate pairs combined into a single character. The
+/// error offset is relative to the start of `s`.
+///
+/// ```rust
+/// use const_json::unescape_str;
+///
+/// assert_eq!(unescape_str(r#"tab\tquote\" é"#).unwrap(), "tab\tquote\" é");
+///
+/// let emoji = unescape_str(r"\uD83D\uDE00").unwrap();
+/// assert_eq!(emoji, "😀");
+/// assert_eq!(emoji.chars().count(), 1);
+///
+/// assert!(unescape_str(r"\x").is_err());
+/// assert!(unescape_str(r"\u12").is_err());
+/// assert!(unescape_str(r"\uD83D").is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn unescape_str(s: &str) -> Result<String, ParseError> {
+    unescape(s, false).map_err(|(kind, offset)| ParseError::new(kind, offset, s.as_bytes()))
+}
+
+/// Unescapes `s`, failing with the offset into `s` of the first bad escape. `\'` is only accepted
+/// if `single_quotes` is set.
+#[cfg(feature = "alloc")]
+pub(crate) fn unescape(s: &str, single_quotes: bool) -> Result<String, (ParseErrorKind, usize)> {
+    let bytes = s.as_bytes();
+    let mut out = String::with_capacity(s.len());
+    let mut run = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] != b'\\' {
+            idx += 1;
+            continue;
+        }
+        out.push_str(&s[run..idx]);
+        let (c, len) = decode_escape(bytes, idx, single_quotes).map_err(|kind| (kind, idx))?;
+        out.push(c);
+        idx += len;
+        run = idx;
+    }
+    out.push_str(&s[run..]);
+    Ok(out)
+}
+
+/// Decodes the escape sequence whose backslash is at `bytes[at]`, returning the character it
+/// stands for and its length in bytes.
+///
+/// A `\uXXXX` escape of a UTF-16 high surrogate must be directly followed by one of a low
+/// surrogate, and the pair decodes to a single character.
+#[cfg(feature = "alloc")]
+pub(crate) fn decode_escape(
+    bytes: &[u8],
+    at: usize,
+    single_quotes: bool,
+) -> Result<(char, usize), ParseErrorKind> {
+    let c = match bytes.get(at + 1) {
+        Some(b'"') => '"',
+        Some(b'\\') => '\\',
+        Some(b'/') => '/',
+        Some(b'\'') if single_quotes => '\'',
+        Some(b'b') => '\u{8}',
+        Some(b'f') => '\u{c}',
+        Some(b'n') => '\n',
+        Some(b'r') => '\r',
+        Some(b't') => '\t',
+        Some(b'u') => {
+            let high = hex4(bytes, at + 2).ok_or(ParseErrorKind::InvalidUnicodeEscape)?;
+            return match high {
+                0xd800..=0xdbff => {
+                    let low = match bytes.get(at + 6..at + 8) {
+                        Some(b"\\u") => {
+                            Some(hex4(bytes, at + 8).ok_or(ParseErrorKind::InvalidUnicodeEscape)?)
+                        }
+                        _ => None,
+                    };
+                    match low {
+                        Some(low @ 0xdc00..=0xdfff) => {
+                            let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                            char::from_u32(code)
+                                .map(|c| (c, 12))
+                                .ok_or(ParseErrorKind::LoneSurrogate)
+                        }
+                        _ => Err(ParseErrorKind::LoneSurrogate),
+                    }
+                }
+                _ => char::from_u32(high)
+                    .map(|c| (c, 6))
+                    .ok_or(ParseErrorKind::LoneSurrogate),
+            };
+        }
+        _ => return Err(ParseErrorKind::InvalidEscape),
+    };
+    Ok((c, 2))
+}
+
+#[cfg(feature = "alloc")]
+fn hex4(bytes: &[u8], at: usize) -> Option<u32> {
+    let digits = bytes.get(at..at + 4)?;
+    let mut value = 0;
+    for &b in digits {
+        value = value * 16 + (b as char).to_digit(16)?;
+    }
+    Some(value)
+}
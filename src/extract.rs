@@ -0,0 +1,177 @@
+//! Reading typed values at a path of keys, with errors that say where the path failed.
+
+use core::fmt;
+
+use crate::{Json, JsonError};
+
+/// An error from [`Json::extract`] and the typed methods like it, saying which part of the path
+/// failed and why.
+///
+/// The first [`at`](Self::at) segments of [`path`](Self::path) lead to the value at fault, and
+/// [`error`](Self::error) says what was wrong with it:
+/// - [`JsonError::KeyNotFound`]: the last of those segments is a key the object lacks
+/// - [`JsonError::WrongType`] with `at` less than the length of the path: a value partway along is
+///   not an object, so the next key cannot be looked up
+/// - [`JsonError::WrongType`] with `at` equal to the length of the path: the value at the end is
+///   not of the type asked for
+///
+/// ```rust
+/// use const_json::{Json, JsonError, const_json};
+///
+/// const CONFIG: Json = const_json!({"server": {"ports": [80, 443]}});
+///
+/// let err = CONFIG.extract_i64(&["server", "ports", "http"]).unwrap_err();
+/// assert_eq!(err.at, 2);
+/// assert_eq!(err.to_string(), "at `server.ports`: expected object, found array");
+///
+/// let err = CONFIG.extract_i64(&["server", "port"]).unwrap_err();
+/// assert_eq!(err.error, JsonError::KeyNotFound { key: "port" });
+/// assert_eq!(err.to_string(), "at `server.port`: key 'port' not found");
+///
+/// let err = CONFIG.extract_str(&["server"]).unwrap_err();
+/// assert_eq!(err.to_string(), "at `server`: expected string, found object");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathError<'p> {
+    /// The whole path that was being read.
+    pub path: &'p [&'p str],
+    /// How many segments of [`path`](Self::path) lead to the value at fault.
+    pub at: usize,
+    /// What went wrong there.
+    pub error: JsonError<'p>,
+}
+
+impl fmt::Display for PathError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path[..self.at].split_first() {
+            Some((first, rest)) => {
+                write!(f, "at `{first}")?;
+                for key in rest {
+                    write!(f, ".{key}")?;
+                }
+                write!(f, "`: {}", self.error)
+            }
+            None => write!(f, "at the root: {}", self.error),
+        }
+    }
+}
+
+impl<'a> Json<'a> {
+    /// Follows `path` through nested objects, one key per segment, in a `const` context. An empty
+    /// path gives `self`. If several entries match a key, the first is used, as with
+    /// [`get_val`](Self::get_val).
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"server": {"tls": {"enabled": true}}});
+    /// const TLS: &Json = match CONFIG.extract(&["server", "tls"]) {
+    ///     Ok(tls) => tls,
+    ///     Err(_) => panic!("no TLS settings"),
+    /// };
+    ///
+    /// assert!(TLS["enabled"].bool());
+    /// assert_eq!(CONFIG.extract(&[]), Ok(&CONFIG));
+    /// ```
+    pub const fn extract<'j, 'p>(&'j self, path: &'p [&'p str]) -> Result<&'j Self, PathError<'p>> {
+        let mut json = self;
+        let mut at = 0;
+        while at < path.len() {
+            let Self::Object(obj) = json else {
+                return Err(PathError {
+                    path,
+                    at,
+                    error: JsonError::WrongType {
+                        expected: "object",
+                        found: json.type_name(),
+                    },
+                });
+            };
+            let mut idx = 0;
+            loop {
+                if idx == obj.len() {
+                    return Err(PathError {
+                        path,
+                        at: at + 1,
+                        error: JsonError::KeyNotFound { key: path[at] },
+                    });
+                }
+                if Self::string_eq(obj[idx].0, path[at]) {
+                    json = &obj[idx].1;
+                    break;
+                }
+                idx += 1;
+            }
+            at += 1;
+        }
+        Ok(json)
+    }
+
+    /// Reads a bool at `path`, following [`extract`](Self::extract).
+    pub const fn extract_bool<'p>(&self, path: &'p [&'p str]) -> Result<bool, PathError<'p>> {
+        match self.extract(path) {
+            Ok(Self::Bool(b)) => Ok(*b),
+            Ok(json) => Err(leaf_error(path, "bool", json)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads an integer at `path`, following [`extract`](Self::extract). Floats are not accepted.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"server": {"port": 8080, "timeout": 1.5}});
+    /// const PORT: i64 = match CONFIG.extract_i64(&["server", "port"]) {
+    ///     Ok(port) => port,
+    ///     Err(_) => panic!("bad port"),
+    /// };
+    ///
+    /// assert_eq!(PORT, 8080);
+    /// assert!(CONFIG.extract_i64(&["server", "timeout"]).is_err());
+    /// ```
+    pub const fn extract_i64<'p>(&self, path: &'p [&'p str]) -> Result<i64, PathError<'p>> {
+        match self.extract(path) {
+            Ok(Self::Int(i)) => Ok(*i),
+            Ok(json) => Err(leaf_error(path, "int", json)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads a number at `path` as an `f64`, following [`extract`](Self::extract). Integers are
+    /// converted, as by [`float`](Self::float).
+    pub const fn extract_f64<'p>(&self, path: &'p [&'p str]) -> Result<f64, PathError<'p>> {
+        match self.extract(path) {
+            Ok(json) => match json.number() {
+                Some(number) => Ok(number.as_f64()),
+                None => Err(leaf_error(path, "float", json)),
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads a string at `path`, following [`extract`](Self::extract).
+    pub const fn extract_str<'p>(&self, path: &'p [&'p str]) -> Result<&'a str, PathError<'p>> {
+        match self.extract(path) {
+            Ok(Self::Str(s)) => Ok(s),
+            Ok(json) => Err(leaf_error(path, "string", json)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// The error for finding `json`, of the wrong type, at the end of `path`.
+const fn leaf_error<'p>(
+    path: &'p [&'p str],
+    expected: &'static str,
+    found: &Json<'_>,
+) -> PathError<'p> {
+    PathError {
+        path,
+        at: path.len(),
+        error: JsonError::WrongType {
+            expected,
+            found: found.type_name(),
+        },
+    }
+}
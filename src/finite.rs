@@ -0,0 +1,141 @@
+//! Values known to hold no NaN or infinite floats, which can be totally ordered.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Write};
+use core::iter;
+use core::ops::Deref;
+use core::slice;
+
+use crate::Json;
+use crate::pointer::push_token;
+
+/// A [`Json`] value with no NaN or infinite floats anywhere inside it.
+///
+/// The derived [`PartialEq`] and [`PartialOrd`] of [`Json`] only fail to be total because of NaN,
+/// so with that ruled out this implements [`Eq`] and [`Ord`], ordering the same way, and can be a
+/// key of a [`BTreeMap`](alloc::collections::BTreeMap) or be sorted. It dereferences to the
+/// wrapped value for everything else.
+///
+/// ```rust
+/// use const_json::{FiniteJson, Json, const_json};
+///
+/// const SCORES: [Json; 3] = [const_json!([2, 0.5]), const_json!([1, 9.5]), const_json!([2, 0.25])];
+///
+/// let mut sorted = SCORES.map(|score| FiniteJson::try_from(score).unwrap());
+/// sorted.sort();
+/// assert_eq!(sorted.map(|score| score.get_idx(1).float()), [9.5, 0.25, 0.5]);
+/// ```
+///
+/// Conversion fails with a JSON Pointer to the first non-finite float:
+///
+/// ```rust
+/// use const_json::{FiniteJson, Json, const_json};
+///
+/// const STATS: Json = const_json!({"runs": [{"mean": 1.5}, {"mean": (f64::NAN)}]});
+///
+/// let err = FiniteJson::try_from(STATS).unwrap_err();
+/// assert_eq!(err.pointer, "/runs/1/mean");
+/// assert_eq!(err.to_string(), "non-finite float NaN at '/runs/1/mean'");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FiniteJson<'a>(Json<'a>);
+
+impl<'a> FiniteJson<'a> {
+    /// Unwraps the value.
+    pub const fn into_inner(self) -> Json<'a> {
+        self.0
+    }
+}
+
+impl<'a> TryFrom<Json<'a>> for FiniteJson<'a> {
+    type Error = NonFiniteError;
+
+    fn try_from(json: Json<'a>) -> Result<Self, Self::Error> {
+        let mut pointer = String::new();
+        match find_non_finite(&json, &mut pointer) {
+            Some(value) => Err(NonFiniteError { pointer, value }),
+            None => Ok(Self(json)),
+        }
+    }
+}
+
+impl<'a> Deref for FiniteJson<'a> {
+    type Target = Json<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Eq for FiniteJson<'_> {}
+
+impl PartialOrd for FiniteJson<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FiniteJson<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).expect("only NaN is unordered")
+    }
+}
+
+/// The error from converting a [`Json`] holding a NaN or infinite float into a [`FiniteJson`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NonFiniteError {
+    /// A JSON Pointer to the first non-finite float, in document order.
+    pub pointer: String,
+    /// The float itself.
+    pub value: f64,
+}
+
+impl fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "non-finite float {} at '{}'", self.value, self.pointer)
+    }
+}
+
+/// Finds the first non-finite float in `json`, leaving `pointer` pointing to it if there is one.
+fn find_non_finite(json: &Json<'_>, pointer: &mut String) -> Option<f64> {
+    // The containers being searched, innermost last, with the length of the pointer to each, so
+    // very deep values cannot overflow the call stack
+    let mut stack = Vec::new();
+    let mut next = Some(json);
+    loop {
+        if let Some(json) = next {
+            match *json {
+                Json::Float(f) if !f.is_finite() => return Some(f),
+                Json::Array(arr) => {
+                    stack.push((Children::Array(arr.iter().enumerate()), pointer.len()))
+                }
+                Json::Object(obj) => stack.push((Children::Object(obj.iter()), pointer.len())),
+                _ => {}
+            }
+        }
+        let (children, len) = stack.last_mut()?;
+        pointer.truncate(*len);
+        next = match children {
+            Children::Array(elems) => elems.next().map(|(idx, elem)| {
+                // Writing to a `String` cannot fail
+                let _ = write!(pointer, "/{idx}");
+                elem
+            }),
+            Children::Object(entries) => entries.next().map(|(key, value)| {
+                push_token(pointer, key);
+                value
+            }),
+        };
+        if next.is_none() {
+            stack.pop();
+        }
+    }
+}
+
+/// The values left to search in an array or object.
+enum Children<'j, 'a> {
+    Array(iter::Enumerate<slice::Iter<'j, Json<'a>>>),
+    Object(slice::Iter<'j, (&'a str, Json<'a>)>),
+}
@@ -0,0 +1,207 @@
+//! Converting between nested objects and flat objects whose keys join the nested keys.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+
+use crate::{Json, OwnedJson};
+
+impl Json<'_> {
+    /// Flattens nested objects into a single object, joining the keys at each level with `sep`,
+    /// so `{"a": {"b": 1}}` becomes `{"a.b": 1}` with a `sep` of `"."`. Entries keep their order,
+    /// each nested object's entries taking its place.
+    ///
+    /// Only objects are flattened: arrays, scalars and empty objects are kept as values. If
+    /// `self` is not a [`Json::Object`], it is returned as it is.
+    ///
+    /// A key that already contains `sep` is copied as it is, so the result is ambiguous: both
+    /// `{"a.b": 1}` and `{"a": {"b": 1}}` flatten to `{"a.b": 1}`, and an object holding both
+    /// flattens to one with two `"a.b"` entries.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({
+    ///     "server": {"http": {"port": 80, "hosts": ["a", "b"]}, "tls": {}},
+    ///     "debug": false
+    /// });
+    ///
+    /// assert_eq!(CONFIG.flatten_keys("."), const_json!({
+    ///     "server.http.port": 80,
+    ///     "server.http.hosts": ["a", "b"],
+    ///     "server.tls": {},
+    ///     "debug": false
+    /// }));
+    /// assert_eq!(CONFIG.flatten_keys("__")["server__http__port"], const_json!(80));
+    /// assert_eq!(const_json!([{"a": 1}]).flatten_keys("."), const_json!([{"a": 1}]));
+    /// ```
+    pub fn flatten_keys(&self, sep: &str) -> OwnedJson {
+        let Self::Object(obj) = *self else {
+            return self.into();
+        };
+        let mut flat = Vec::new();
+        flatten_into(&mut flat, obj, sep);
+        OwnedJson::Object(flat)
+    }
+}
+
+/// Pushes the flattened entries of `obj` onto `flat`.
+fn flatten_into(flat: &mut Vec<(String, OwnedJson)>, obj: &[(&str, Json<'_>)], sep: &str) {
+    // The objects being flattened, innermost last, each with the length of the prefix before its
+    // keys, so very deep values cannot overflow the call stack
+    let mut prefix = String::new();
+    let mut stack = Vec::from([(obj.iter(), 0)]);
+    while let Some((entries, len)) = stack.last_mut() {
+        prefix.truncate(*len);
+        let Some((key, value)) = entries.next() else {
+            stack.pop();
+            continue;
+        };
+        prefix.push_str(key);
+        match *value {
+            Json::Object(inner @ [_, ..]) => {
+                prefix.push_str(sep);
+                stack.push((inner.iter(), prefix.len()));
+            }
+            _ => flat.push((prefix.clone(), value.into())),
+        }
+    }
+}
+
+impl OwnedJson {
+    /// Expands an object whose keys join nested keys with `sep` into nested objects, so
+    /// `{"a.b.c": 1}` becomes `{"a": {"b": {"c": 1}}}` with a `sep` of `"."`. This reverses
+    /// [`Json::flatten_keys`]. Objects are created in the order their first key appears, and keys
+    /// without `sep` are kept as they are. Values are not expanded, and if `self` is not an
+    /// [`OwnedJson::Object`], it is returned as it is.
+    ///
+    /// Fails if two keys lead to the same place, or one key leads inside another's value, as with
+    /// `{"a": 1, "a.b": 2}`. Only the objects made here are stepped into, never values, even
+    /// objects, so `{"a": {"x": 1}, "a.b": 2}` fails whichever order its keys come in. The error
+    /// holds the later of the two keys.
+    ///
+    /// ```rust
+    /// use const_json::{Json, OwnedJson, const_json};
+    ///
+    /// let env = OwnedJson::from(const_json!({
+    ///     "db.host": "localhost",
+    ///     "db.pool.size": 4,
+    ///     "debug": true,
+    ///     "db.pool.idle": [30]
+    /// }));
+    /// assert_eq!(env.unflatten_keys(".").unwrap(), const_json!({
+    ///     "db": {"host": "localhost", "pool": {"size": 4, "idle": [30]}},
+    ///     "debug": true
+    /// }));
+    ///
+    /// const CONFIG: Json = const_json!({"server": {"http": {"port": 80}, "tls": {}}, "name": "x"});
+    /// assert_eq!(CONFIG.flatten_keys("__").unflatten_keys("__").unwrap(), CONFIG);
+    ///
+    /// let conflict = OwnedJson::from(const_json!({"db": "x", "db.host": "y"}));
+    /// let err = conflict.unflatten_keys(".").unwrap_err();
+    /// assert_eq!(err.key, "db.host");
+    /// assert_eq!(err.to_string(), "key 'db.host' conflicts with an earlier key");
+    ///
+    /// let before = OwnedJson::from(const_json!({"db": {"port": 1}, "db.host": "y"}));
+    /// assert_eq!(before.unflatten_keys(".").unwrap_err().key, "db.host");
+    /// let after = OwnedJson::from(const_json!({"db.host": "y", "db": {"port": 1}}));
+    /// assert_eq!(after.unflatten_keys(".").unwrap_err().key, "db");
+    /// ```
+    pub fn unflatten_keys(&self, sep: &str) -> Result<OwnedJson, UnflattenError> {
+        let Self::Object(flat) = self else {
+            return Ok(self.clone());
+        };
+        let mut root = Nested::default();
+        for (key, value) in flat {
+            let conflict = || UnflattenError { key: key.clone() };
+            let mut parts: Vec<&str> = match sep {
+                "" => Vec::from([key.as_str()]),
+                sep => key.split(sep).collect(),
+            };
+            let last = parts.pop().unwrap_or_default();
+            let mut obj = &mut root;
+            for part in parts {
+                let idx = match obj.index.get(part) {
+                    Some(&idx) => idx,
+                    None => obj.push(part, Node::Nested(Nested::default())),
+                };
+                obj = match &mut obj.entries[idx].1 {
+                    Node::Nested(inner) => inner,
+                    Node::Value(_) => return Err(conflict()),
+                };
+            }
+            if obj.index.contains_key(last) {
+                return Err(conflict());
+            }
+            obj.push(last, Node::Value(value.clone()));
+        }
+        Ok(root.into_json())
+    }
+}
+
+/// A place in the objects that [`OwnedJson::unflatten_keys`] is building.
+enum Node {
+    Nested(Nested),
+    /// A value from the flat object, which is never stepped into.
+    Value(OwnedJson),
+}
+
+/// An object made to hold the entries under a key prefix.
+#[derive(Default)]
+struct Nested {
+    entries: Vec<(String, Node)>,
+    /// The index of each key in `entries`.
+    index: BTreeMap<String, usize>,
+}
+
+impl Nested {
+    /// Adds an entry with a new key, giving its index.
+    fn push(&mut self, key: &str, node: Node) -> usize {
+        let idx = self.entries.len();
+        self.index.insert(key.into(), idx);
+        self.entries.push((key.into(), node));
+        idx
+    }
+
+    fn into_json(self) -> OwnedJson {
+        // The objects being built around the current one, with the entries left, the entries
+        // built so far and the key of the current one, so long keys cannot overflow the call
+        // stack
+        let mut stack = Vec::new();
+        let mut entries = self.entries.into_iter();
+        let mut built = Vec::new();
+        loop {
+            match entries.next() {
+                Some((key, Node::Value(value))) => built.push((key, value)),
+                Some((key, Node::Nested(inner))) => {
+                    let outer = mem::replace(&mut entries, inner.entries.into_iter());
+                    stack.push((outer, mem::take(&mut built), key));
+                }
+                None => {
+                    let obj = OwnedJson::Object(built);
+                    let Some((outer, mut outer_built, key)) = stack.pop() else {
+                        return obj;
+                    };
+                    outer_built.push((key, obj));
+                    (entries, built) = (outer, outer_built);
+                }
+            }
+        }
+    }
+}
+
+/// The error from [`OwnedJson::unflatten_keys`] when two keys lead to the same place, or one
+/// leads inside the other's value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnflattenError {
+    /// The later of the two keys.
+    pub key: String,
+}
+
+impl fmt::Display for UnflattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key '{}' conflicts with an earlier key", self.key)
+    }
+}
@@ -0,0 +1,519 @@
+//! Conversions between decimal text and `f64`, in a `const` context: correctly rounded parsing,
+//! and formatting with the fewest digits that parse back to the same value.
+
+/// How many significant digits are kept exactly. Every halfway point between two adjacent `f64`s
+/// can be written out in fewer digits than this, so the digits past it only matter for whether
+/// they are all zero.
+const MAX_DIGITS: usize = 768;
+
+/// Enough 32-bit limbs for the largest numbers compared during conversion: 769 digits scaled by a
+/// power of ten and a power of two, which needs a little over 3700 bits.
+const LIMBS: usize = 128;
+
+/// The powers of ten that are exactly representable as an `f64`.
+const POW10: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+/// A fixed-size unsigned big integer, least significant limb first. Only the first `len` limbs
+/// can be nonzero, which keeps arithmetic on small values cheap.
+#[derive(Clone, Copy)]
+struct Big {
+    limbs: [u32; LIMBS],
+    len: usize,
+}
+
+impl Big {
+    const fn new(value: u32) -> Self {
+        let mut limbs = [0; LIMBS];
+        limbs[0] = value;
+        Self { limbs, len: 1 }
+    }
+
+    /// Sets `self` to `self * mul + add`.
+    const fn mul_add(&mut self, mul: u32, add: u32) {
+        let mut carry = add as u64;
+        let mut idx = 0;
+        while idx < self.len {
+            let wide = self.limbs[idx] as u64 * mul as u64 + carry;
+            self.limbs[idx] = wide as u32;
+            carry = wide >> 32;
+            idx += 1;
+        }
+        if carry != 0 {
+            self.limbs[self.len] = carry as u32;
+            self.len += 1;
+        }
+    }
+
+    const fn from_u64(value: u64) -> Self {
+        let mut big = Self::new(value as u32);
+        big.limbs[1] = (value >> 32) as u32;
+        big.len = 2;
+        big
+    }
+
+    const fn mul_pow5(&mut self, mut exp: usize) {
+        while exp >= 13 {
+            self.mul_add(1_220_703_125, 0);
+            exp -= 13;
+        }
+        let mut mul = 1;
+        while exp > 0 {
+            mul *= 5;
+            exp -= 1;
+        }
+        self.mul_add(mul, 0);
+    }
+
+    /// Sets `self` to `self / div`, returning the remainder.
+    const fn div_small(&mut self, div: u32) -> u32 {
+        let mut rem: u64 = 0;
+        let mut idx = self.len;
+        while idx > 0 {
+            idx -= 1;
+            let wide = rem << 32 | self.limbs[idx] as u64;
+            self.limbs[idx] = (wide / div as u64) as u32;
+            rem = wide % div as u64;
+        }
+        while self.len > 1 && self.limbs[self.len - 1] == 0 {
+            self.len -= 1;
+        }
+        rem as u32
+    }
+
+    const fn mul_pow10(&mut self, mut exp: usize) {
+        while exp >= 9 {
+            self.mul_add(1_000_000_000, 0);
+            exp -= 9;
+        }
+        let mut mul = 1;
+        while exp > 0 {
+            mul *= 10;
+            exp -= 1;
+        }
+        self.mul_add(mul, 0);
+    }
+
+    const fn shl(&mut self, bits: usize) {
+        let (limbs, bits) = (bits / 32, bits % 32);
+        let len = self.len + limbs + 1;
+        let mut idx = len;
+        while idx > 0 {
+            idx -= 1;
+            let mut limb = 0;
+            if idx >= limbs {
+                limb = self.limbs[idx - limbs] << bits;
+                if bits > 0 && idx > limbs {
+                    limb |= self.limbs[idx - limbs - 1] >> (32 - bits);
+                }
+            }
+            self.limbs[idx] = limb;
+        }
+        self.len = len;
+    }
+
+    const fn shr1(&mut self) {
+        let mut idx = 0;
+        while idx < self.len {
+            self.limbs[idx] >>= 1;
+            if idx + 1 < self.len {
+                self.limbs[idx] |= self.limbs[idx + 1] << 31;
+            }
+            idx += 1;
+        }
+    }
+
+    const fn bit_len(&self) -> usize {
+        let mut idx = self.len;
+        while idx > 0 {
+            idx -= 1;
+            if self.limbs[idx] != 0 {
+                return idx * 32 + (32 - self.limbs[idx].leading_zeros() as usize);
+            }
+        }
+        0
+    }
+
+    const fn is_zero(&self) -> bool {
+        self.bit_len() == 0
+    }
+
+    const fn ge(&self, other: &Self) -> bool {
+        let mut idx = if self.len > other.len {
+            self.len
+        } else {
+            other.len
+        };
+        while idx > 0 {
+            idx -= 1;
+            if self.limbs[idx] != other.limbs[idx] {
+                return self.limbs[idx] > other.limbs[idx];
+            }
+        }
+        true
+    }
+
+    /// Sets `self` to `self - other`, which must not be negative.
+    const fn sub(&mut self, other: &Self) {
+        let mut borrow = false;
+        let mut idx = 0;
+        while idx < other.len {
+            let (limb, b1) = self.limbs[idx].overflowing_sub(other.limbs[idx]);
+            let (limb, b2) = limb.overflowing_sub(borrow as u32);
+            self.limbs[idx] = limb;
+            borrow = b1 || b2;
+            idx += 1;
+        }
+        while borrow {
+            let (limb, b) = self.limbs[idx].overflowing_sub(1);
+            self.limbs[idx] = limb;
+            borrow = b;
+            idx += 1;
+        }
+    }
+
+    const fn low_u64(&self) -> u64 {
+        self.limbs[0] as u64 | (self.limbs[1] as u64) << 32
+    }
+}
+
+/// Converts a number matching the JSON number grammar to the nearest `f64`, rounding ties to
+/// even, exactly as `str::parse` does.
+pub(crate) const fn parse_f64(text: &[u8]) -> f64 {
+    let neg = text[0] == b'-';
+    let mut idx = neg as usize;
+
+    // The value is `digits * 10^exp`, where `digits` has `count` significant digits
+    let mut digits = Big::new(0);
+    let mut count = 0;
+    let mut exp: i64 = 0;
+    let mut truncated = false;
+    let mut fraction = false;
+    while idx < text.len() {
+        let b = text[idx];
+        if b == b'.' {
+            fraction = true;
+        } else if b.is_ascii_digit() {
+            let digit = b - b'0';
+            if count < MAX_DIGITS {
+                if count > 0 || digit != 0 {
+                    digits.mul_add(10, digit as u32);
+                    count += 1;
+                }
+                if fraction {
+                    exp -= 1;
+                }
+            } else {
+                truncated |= digit != 0;
+                if !fraction {
+                    exp += 1;
+                }
+            }
+        } else {
+            break;
+        }
+        idx += 1;
+    }
+
+    if idx < text.len() {
+        // Skip the `e` or `E`
+        idx += 1;
+        let exp_neg = text[idx] == b'-';
+        if text[idx] == b'-' || text[idx] == b'+' {
+            idx += 1;
+        }
+        let mut written: i64 = 0;
+        while idx < text.len() {
+            // Anything this large overflows or underflows either way
+            if written < 1_000_000 {
+                written = written * 10 + (text[idx] - b'0') as i64;
+            }
+            idx += 1;
+        }
+        exp += if exp_neg { -written } else { written };
+    }
+
+    if truncated {
+        // Stands in for the nonzero digits that were dropped: the value is still strictly between
+        // the same two halfway points
+        digits.mul_add(10, 1);
+        count += 1;
+        exp -= 1;
+    }
+
+    let magnitude = if count == 0 || count as i64 + exp < -324 {
+        0.0
+    } else if count as i64 + exp > 310 {
+        f64::INFINITY
+    } else if count <= 15 && exp >= -22 && exp <= 22 {
+        // Both operands are exact, so the single rounding step gives the right answer
+        let digits = digits.low_u64() as f64;
+        if exp >= 0 {
+            digits * POW10[exp as usize]
+        } else {
+            digits / POW10[-exp as usize]
+        }
+    } else {
+        slow_path(digits, exp)
+    };
+    if neg { -magnitude } else { magnitude }
+}
+
+/// Converts `digits * 10^exp` by dividing big integers, which is exact but slow.
+const fn slow_path(digits: Big, exp: i64) -> f64 {
+    let mut num = digits;
+    let mut den = Big::new(1);
+    if exp >= 0 {
+        num.mul_pow10(exp as usize);
+    } else {
+        den.mul_pow10(-exp as usize);
+    }
+
+    // Scale so the quotient has 57 or 58 bits, more than enough to round to 53
+    let shift = 57 - (num.bit_len() as i64 - den.bit_len() as i64);
+    if shift > 0 {
+        num.shl(shift as usize);
+    } else {
+        den.shl(-shift as usize);
+    }
+
+    let mut quotient: u64 = 0;
+    den.shl(57);
+    let mut bit = 58;
+    while bit > 0 {
+        bit -= 1;
+        if num.ge(&den) {
+            num.sub(&den);
+            quotient |= 1 << bit;
+        }
+        den.shr1();
+    }
+    let sticky = !num.is_zero();
+
+    // The value is `quotient * 2^-shift`, which lies in `[2^bin_exp, 2^(bin_exp + 1))`
+    let len = 64 - quotient.leading_zeros() as i64;
+    let mut bin_exp = len - 1 - shift;
+    if bin_exp > 1023 {
+        return f64::INFINITY;
+    }
+    let keep = if bin_exp >= -1022 {
+        53
+    } else {
+        53 - (-1022 - bin_exp)
+    };
+    if keep < 0 {
+        return 0.0;
+    }
+
+    let drop = (len - keep) as u32;
+    let mut mantissa = quotient >> drop;
+    let half = (quotient >> (drop - 1)) & 1 == 1;
+    let rest = quotient & ((1 << (drop - 1)) - 1) != 0 || sticky;
+    if half && (rest || mantissa & 1 == 1) {
+        mantissa += 1;
+    }
+
+    if bin_exp < -1022 {
+        // Subnormal. Rounding up to 2^52 carries into the exponent field, giving the smallest
+        // normal number as it should
+        return f64::from_bits(mantissa);
+    }
+    if mantissa == 1 << 53 {
+        mantissa >>= 1;
+        bin_exp += 1;
+        if bin_exp > 1023 {
+            return f64::INFINITY;
+        }
+    }
+    f64::from_bits(((bin_exp + 1023) as u64) << 52 | (mantissa & ((1 << 52) - 1)))
+}
+
+/// The most significant digits an `f64` can need to round trip.
+const MAX_SHORTEST: usize = 17;
+
+/// Enough digits for the exact decimal expansion of any `f64`.
+const MAX_EXACT: usize = 800;
+
+/// Formats a finite float exactly as `{:e}` does, as the fewest significant digits that parse
+/// back to `f`, choosing the closest such digits if there is a choice: an optional `-`, then
+/// `d.ddd`, or just `d`, then `e` and the decimal exponent.
+pub(crate) const fn format_exp(f: f64) -> ([u8; 32], usize) {
+    let mut out = [0; 32];
+    let mut len = 0;
+    if f.is_sign_negative() {
+        out[0] = b'-';
+        len = 1;
+    }
+    let (digits, count, exp) = shortest(f.abs());
+    out[len] = digits[0];
+    len += 1;
+    if count > 1 {
+        out[len] = b'.';
+        len += 1;
+        let mut idx = 1;
+        while idx < count {
+            out[len] = digits[idx];
+            len += 1;
+            idx += 1;
+        }
+    }
+    out[len] = b'e';
+    len += 1;
+    len = push_exp(&mut out, len, exp);
+    (out, len)
+}
+
+/// Writes `exp` in decimal at `out[len..]`, returning the new length.
+const fn push_exp(out: &mut [u8], mut len: usize, exp: i64) -> usize {
+    if exp < 0 {
+        out[len] = b'-';
+        len += 1;
+    }
+    let mut magnitude = exp.unsigned_abs();
+    let mut digits = [0; 4];
+    let mut count = 0;
+    loop {
+        digits[count] = b'0' + (magnitude % 10) as u8;
+        count += 1;
+        magnitude /= 10;
+        if magnitude == 0 {
+            break;
+        }
+    }
+    while count > 0 {
+        count -= 1;
+        out[len] = digits[count];
+        len += 1;
+    }
+    len
+}
+
+/// The shortest digits of a finite, non-negative `f`, how many there are, and the decimal
+/// exponent of the first.
+const fn shortest(f: f64) -> ([u8; MAX_SHORTEST], usize, i64) {
+    let mut digits = [0; MAX_SHORTEST];
+    if f == 0.0 {
+        digits[0] = b'0';
+        return (digits, 1, 0);
+    }
+
+    // `f` is exactly `mantissa * 2^exp`, which is `whole * 10^scale` for an integer `whole`
+    let bits = f.to_bits();
+    let biased = (bits >> 52) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mantissa, exp) = match biased {
+        0 => (fraction, -1074),
+        _ => (fraction | 1 << 52, biased - 1075),
+    };
+    let mut whole = Big::from_u64(mantissa);
+    let scale = if exp >= 0 {
+        whole.shl(exp as usize);
+        0
+    } else {
+        whole.mul_pow5(-exp as usize);
+        exp
+    };
+
+    // Every digit of `whole`, written backwards from the end of the buffer
+    let mut exact = [0; MAX_EXACT];
+    let mut start = MAX_EXACT;
+    while !whole.is_zero() {
+        let mut chunk = whole.div_small(1_000_000_000);
+        let mut idx = 0;
+        while idx < 9 {
+            start -= 1;
+            exact[start] = b'0' + (chunk % 10) as u8;
+            chunk /= 10;
+            idx += 1;
+        }
+    }
+    while exact[start] == b'0' {
+        start += 1;
+    }
+    let mut end = MAX_EXACT;
+    while exact[end - 1] == b'0' {
+        end -= 1;
+    }
+    let first_exp = (MAX_EXACT - start) as i64 - 1 + scale;
+    let exact = sub(&exact, start, end);
+
+    let mut count = 1;
+    while count < exact.len() {
+        // Rounding down keeps the first `count` digits, and rounding up adds one to the last
+        let mut up = [0; MAX_SHORTEST];
+        let mut up_exp = first_exp;
+        let mut idx = 0;
+        while idx < count {
+            digits[idx] = exact[idx];
+            up[idx] = exact[idx];
+            idx += 1;
+        }
+        idx = count;
+        loop {
+            if idx == 0 {
+                // Every digit was a 9
+                up[0] = b'1';
+                up_exp += 1;
+                break;
+            }
+            idx -= 1;
+            if up[idx] == b'9' {
+                up[idx] = b'0';
+            } else {
+                up[idx] += 1;
+                break;
+            }
+        }
+
+        let down_ok = round_trips(&digits, count, first_exp, f);
+        let up_ok = round_trips(&up, count, up_exp, f);
+        // The first dropped digit says which way is closer, and a tie goes up
+        let up_closer = exact[count] >= b'5';
+        if up_ok && (up_closer || !down_ok) {
+            let mut len = count;
+            while up[len - 1] == b'0' {
+                len -= 1;
+            }
+            return (up, len, up_exp);
+        } else if down_ok {
+            let mut len = count;
+            while digits[len - 1] == b'0' {
+                len -= 1;
+            }
+            return (digits, len, first_exp);
+        }
+        count += 1;
+    }
+
+    let mut idx = 0;
+    while idx < exact.len() {
+        digits[idx] = exact[idx];
+        idx += 1;
+    }
+    (digits, exact.len(), first_exp)
+}
+
+/// Whether `digits[..count]`, with the first digit at decimal exponent `exp`, parses to `f`.
+const fn round_trips(digits: &[u8; MAX_SHORTEST], count: usize, exp: i64, f: f64) -> bool {
+    let mut text = [0; MAX_SHORTEST + 8];
+    let mut idx = 0;
+    while idx < count {
+        text[idx] = digits[idx];
+        idx += 1;
+    }
+    text[count] = b'e';
+    let len = push_exp(&mut text, count + 1, exp - (count as i64 - 1));
+    parse_f64(sub(&text, 0, len)) == f
+}
+
+const fn sub(bytes: &[u8], start: usize, end: usize) -> &[u8] {
+    match bytes.split_at_checked(end) {
+        Some((bytes, _)) => match bytes.split_at_checked(start) {
+            Some((_, bytes)) => bytes,
+            None => unreachable!(),
+        },
+        None => unreachable!(),
+    }
+}
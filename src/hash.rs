@@ -0,0 +1,187 @@
+//! Hashing values, for use as keys of hash maps and sets.
+
+use core::hash::{Hash, Hasher};
+use core::slice;
+
+use crate::Json;
+use crate::iter::Entries;
+use crate::ser::Stack;
+
+/// A [`Json`] value with [`Hash`] and [`Eq`] impls that ignore the order of object entries, so
+/// that it can go in a hash set or be a hash map key.
+///
+/// Two wrapped values are equal if:
+/// - they have the same variant, so `Int(1)` and `Float(1.0)` are unequal
+/// - floats have the same bits, so `NaN` equals itself, with the same payload, and `0.0` does not
+///   equal `-0.0`
+/// - arrays have equal elements in the same order, since array order is meaningful in JSON
+/// - objects have the same entries in any order. A key that appears more than once is matched by
+///   occurrence: its first entry in one object against its first entry in the other, and so on.
+///
+/// Values that are equal hash equally, because object entries are hashed sorted by key.
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use const_json::{HashableJson, Json, const_json};
+///
+/// const DOCS: [Json; 4] = [
+///     const_json!({"id": 1, "tags": ["a", "b"]}),
+///     const_json!({"tags": ["a", "b"], "id": 1}),
+///     const_json!({"id": 1, "tags": ["b", "a"]}),
+///     const_json!({"id": 1.0, "tags": ["a", "b"]}),
+/// ];
+///
+/// let unique: HashSet<HashableJson> = DOCS.into_iter().map(HashableJson).collect();
+/// // Only the first two are the same once key order is ignored
+/// assert_eq!(unique.len(), 3);
+/// assert!(unique.contains(&HashableJson(const_json!({"tags": ["b", "a"], "id": 1}))));
+///
+/// assert_eq!(HashableJson(Json::Float(f64::NAN)), HashableJson(Json::Float(f64::NAN)));
+/// assert_ne!(HashableJson(Json::Float(0.0)), HashableJson(Json::Float(-0.0)));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct HashableJson<'a>(pub Json<'a>);
+
+impl PartialEq for HashableJson<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        unordered_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for HashableJson<'_> {}
+
+impl Hash for HashableJson<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_json(&self.0, state);
+    }
+}
+
+/// A pair of arrays or objects that [`unordered_eq`] is comparing, with the elements left.
+enum EqFrame<'j, 'a> {
+    Arrays(slice::Iter<'j, Json<'a>>, slice::Iter<'j, Json<'a>>),
+    /// Both objects' entries sorted by key, so matching entries come in the same place.
+    Objects(Entries<'j, 'a>, Entries<'j, 'a>),
+}
+
+fn unordered_eq(l: &Json<'_>, r: &Json<'_>) -> bool {
+    // The containers being compared, innermost last, so very deep values cannot overflow the call
+    // stack
+    let mut stack = Stack::new();
+    let mut next = Some((l, r));
+    loop {
+        if let Some((l, r)) = next {
+            match (*l, *r) {
+                (Json::Null(()), Json::Null(())) => {}
+                (Json::Bool(l), Json::Bool(r)) if l == r => {}
+                (Json::Int(l), Json::Int(r)) if l == r => {}
+                (Json::Float(l), Json::Float(r)) if l.to_bits() == r.to_bits() => {}
+                (Json::Str(l), Json::Str(r)) if l == r => {}
+                (Json::Array(la), Json::Array(ra)) if la.len() == ra.len() => {
+                    // Without `alloc`, pairs deeper than the stack has room for get a stack of
+                    // their own
+                    if stack.is_full() {
+                        if !unordered_eq(l, r) {
+                            return false;
+                        }
+                    } else {
+                        stack.push(EqFrame::Arrays(la.iter(), ra.iter()));
+                    }
+                }
+                (Json::Object(lo), Json::Object(ro)) if lo.len() == ro.len() => {
+                    if stack.is_full() {
+                        if !unordered_eq(l, r) {
+                            return false;
+                        }
+                    } else {
+                        // Sorting is stable, so entries with the same key are matched by
+                        // occurrence
+                        let (l, r) = (Entries::new(lo, true), Entries::new(ro, true));
+                        stack.push(EqFrame::Objects(l, r));
+                    }
+                }
+                _ => return false,
+            }
+        }
+        let Some(frame) = stack.last_mut() else {
+            return true;
+        };
+        // Both sides have the same length, so they run out together
+        next = match frame {
+            EqFrame::Arrays(l, r) => l.next().zip(r.next()),
+            EqFrame::Objects(l, r) => match l.next().zip(r.next()) {
+                Some(((lk, _), (rk, _))) if lk != rk => return false,
+                pair => pair.map(|((_, l), (_, r))| (l, r)),
+            },
+        };
+        if next.is_none() {
+            stack.pop();
+        }
+    }
+}
+
+/// An array or object that [`hash_json`] is hashing, with the elements left.
+enum HashFrame<'j, 'a> {
+    Array(slice::Iter<'j, Json<'a>>),
+    /// The entries sorted by key, so equal objects hash equally.
+    Object(Entries<'j, 'a>),
+}
+
+fn hash_json<H: Hasher>(json: &Json<'_>, state: &mut H) {
+    // The containers being hashed, innermost last, so very deep values cannot overflow the call
+    // stack
+    let mut stack = Stack::new();
+    let mut next = Some(json);
+    loop {
+        if let Some(value) = next {
+            match *value {
+                Json::Null(()) => state.write_u8(0),
+                Json::Bool(b) => {
+                    state.write_u8(1);
+                    b.hash(state);
+                }
+                Json::Float(f) => {
+                    state.write_u8(2);
+                    f.to_bits().hash(state);
+                }
+                Json::Int(i) => {
+                    state.write_u8(3);
+                    i.hash(state);
+                }
+                Json::Str(s) => {
+                    state.write_u8(4);
+                    s.hash(state);
+                }
+                // Without `alloc`, values deeper than the stack has room for get a stack of
+                // their own
+                Json::Array(_) | Json::Object(_) if stack.is_full() => {
+                    hash_json(value, state);
+                }
+                Json::Array(arr) => {
+                    state.write_u8(5);
+                    state.write_usize(arr.len());
+                    stack.push(HashFrame::Array(arr.iter()));
+                }
+                Json::Object(obj) => {
+                    state.write_u8(6);
+                    state.write_usize(obj.len());
+                    // Sorting is stable, so entries with the same key stay in the order they are
+                    // matched in
+                    stack.push(HashFrame::Object(Entries::new(obj, true)));
+                }
+            }
+        }
+        let Some(frame) = stack.last_mut() else {
+            return;
+        };
+        next = match frame {
+            HashFrame::Array(elems) => elems.next(),
+            HashFrame::Object(entries) => entries.next().map(|(key, value)| {
+                key.hash(state);
+                value
+            }),
+        };
+        if next.is_none() {
+            stack.pop();
+        }
+    }
+}
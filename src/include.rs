@@ -0,0 +1,756 @@
+//! The compile-time parser behind [`include_json`](crate::include_json).
+//!
+//! A `Json` borrows its children, so every array and object needs backing storage with a size
+//! known at compile time. The macro measures the document in a first pass, then builds all of its
+//! array elements and object entries into a single static, which refers to itself for the
+//! children of each container. The values are laid out level by level, so the children of each
+//! container are contiguous.
+//!
+//! Strings without escapes borrow from the source. The decoded bytes of the rest are gathered into
+//! one more static, in document order.
+
+use core::str;
+
+use crate::Json;
+use crate::float::parse_f64;
+
+/// How deeply arrays and objects can be nested in an [`include_json`](crate::include_json)
+/// document. A document nested deeper fails the build with an error saying so, rather than
+/// exhausting the limits of `const` evaluation, since the compile-time parser keeps a fixed-size
+/// stack instead of recursing.
+///
+/// This matches [`ParserOptions::MAX_DEPTH`](crate::ParserOptions::MAX_DEPTH), so anything the
+/// macro accepts can also be parsed at runtime with the default options.
+pub const MAX_DEPTH: usize = 128;
+
+/// The sizes of the arrays backing a document, as computed by [`IncludeShape::of`].
+#[doc(hidden)]
+pub struct IncludeShape {
+    pub elems: usize,
+    pub entries: usize,
+    pub unescaped: usize,
+    /// The number of array elements and object entries at each depth.
+    elems_at: [usize; MAX_DEPTH + 1],
+    entries_at: [usize; MAX_DEPTH + 1],
+}
+
+/// Every array element and object entry in a document, the top-level value first.
+#[doc(hidden)]
+pub type IncludeDoc<const N: usize, const M: usize> =
+    ([Json<'static>; N], [(&'static str, Json<'static>); M]);
+
+impl IncludeShape {
+    /// Validates `src`, failing compilation with a message pointing into `path` if it is not
+    /// valid JSON, and measures it.
+    pub const fn of(src: &str, path: &str) -> Self {
+        let mut shape = Self {
+            elems: 0,
+            entries: 0,
+            unescaped: 0,
+            elems_at: [0; MAX_DEPTH + 1],
+            entries_at: [0; MAX_DEPTH + 1],
+        };
+        let mut parser = Parser::new(src, path);
+        loop {
+            match parser.next() {
+                Event::Item { depth, key, token } => {
+                    if let Some(key) = key {
+                        shape.entries += 1;
+                        shape.entries_at[depth] += 1;
+                        shape.unescaped += key.unescaped_len();
+                    } else {
+                        shape.elems += 1;
+                        shape.elems_at[depth] += 1;
+                    }
+                    if let Token::Str(s) = token {
+                        shape.unescaped += s.unescaped_len();
+                    }
+                }
+                Event::Close { .. } => {}
+                Event::End => return shape,
+            }
+        }
+    }
+}
+
+/// Decodes every string in `src` that contains escapes, one after the other. `N` must be
+/// [`IncludeShape::unescaped`].
+#[doc(hidden)]
+pub const fn include_unescape<const N: usize>(src: &str) -> [u8; N] {
+    let mut buf = [0; N];
+    let mut len = 0;
+    let mut parser = Parser::new(src, "");
+    loop {
+        match parser.next() {
+            Event::Item { key, token, .. } => {
+                if let Some(key) = key {
+                    len = key.unescape_into(src.as_bytes(), &mut buf, len);
+                }
+                if let Token::Str(s) = token {
+                    len = s.unescape_into(src.as_bytes(), &mut buf, len);
+                }
+            }
+            Event::Close { .. } => {}
+            Event::End => return buf,
+        }
+    }
+}
+
+/// Builds the values of `src`. `doc` must be the static being initialized, and `N` and `M` the
+/// [`IncludeShape`] counts.
+#[doc(hidden)]
+pub const fn include_doc<const N: usize, const M: usize>(
+    src: &'static str,
+    unescaped: &'static [u8],
+    shape: &IncludeShape,
+    doc: &'static IncludeDoc<N, M>,
+) -> IncludeDoc<N, M> {
+    let mut elems = [Json::Null(()); N];
+    let mut entries = [("", Json::Null(())); M];
+
+    // Where the next element and entry at each depth go
+    let mut next_elem = [0; MAX_DEPTH + 1];
+    let mut next_entry = [0; MAX_DEPTH + 1];
+    let mut depth = 1;
+    while depth <= MAX_DEPTH {
+        next_elem[depth] = next_elem[depth - 1] + shape.elems_at[depth - 1];
+        next_entry[depth] = next_entry[depth - 1] + shape.entries_at[depth - 1];
+        depth += 1;
+    }
+    // The slot and first child of the container open at each depth
+    let mut open = [(Slot::Elem(0), 0); MAX_DEPTH];
+
+    let unescaped = match str::from_utf8(unescaped) {
+        Ok(s) => s,
+        Err(_) => unreachable!(),
+    };
+    let mut unescaped_pos = 0;
+
+    let mut parser = Parser::new(src, "");
+    loop {
+        match parser.next() {
+            Event::Item { depth, key, token } => {
+                let value = match token {
+                    Token::Null => Json::Null(()),
+                    Token::Bool(b) => Json::Bool(b),
+                    Token::Number { start, end } => number(src.as_bytes(), start, end),
+                    Token::Str(_) => Json::Null(()),
+                    // Filled in once the container is closed
+                    Token::Open { .. } => Json::Null(()),
+                };
+                let slot = match key {
+                    Some(key) => {
+                        let key = key.resolve(src, unescaped, &mut unescaped_pos);
+                        entries[next_entry[depth]] = (key, value);
+                        next_entry[depth] += 1;
+                        Slot::Entry(next_entry[depth] - 1)
+                    }
+                    None => {
+                        elems[next_elem[depth]] = value;
+                        next_elem[depth] += 1;
+                        Slot::Elem(next_elem[depth] - 1)
+                    }
+                };
+                if let Token::Open { object } = token {
+                    let children = if object {
+                        next_entry[depth + 1]
+                    } else {
+                        next_elem[depth + 1]
+                    };
+                    open[depth] = (slot, children);
+                }
+                // The key's decoded bytes come before the value's
+                if let Token::Str(s) = token {
+                    let value = Json::Str(s.resolve(src, unescaped, &mut unescaped_pos));
+                    match slot {
+                        Slot::Elem(idx) => elems[idx] = value,
+                        Slot::Entry(idx) => entries[idx].1 = value,
+                    }
+                }
+            }
+            Event::Close { depth, object } => {
+                let (slot, start) = open[depth];
+                let value = if object {
+                    Json::Object(sub(&doc.1, start, next_entry[depth + 1]))
+                } else {
+                    Json::Array(sub(&doc.0, start, next_elem[depth + 1]))
+                };
+                match slot {
+                    Slot::Elem(idx) => elems[idx] = value,
+                    Slot::Entry(idx) => entries[idx].1 = value,
+                }
+            }
+            Event::End => return (elems, entries),
+        }
+    }
+}
+
+/// Where a value was stored by [`include_doc`].
+#[derive(Clone, Copy)]
+enum Slot {
+    Elem(usize),
+    Entry(usize),
+}
+
+const fn sub<T>(slice: &[T], start: usize, end: usize) -> &[T] {
+    match slice.split_at_checked(end) {
+        Some((slice, _)) => match slice.split_at_checked(start) {
+            Some((_, slice)) => slice,
+            None => unreachable!(),
+        },
+        None => unreachable!(),
+    }
+}
+
+const fn substr(s: &str, start: usize, end: usize) -> &str {
+    match s.split_at_checked(end) {
+        Some((s, _)) => match s.split_at_checked(start) {
+            Some((_, s)) => s,
+            None => unreachable!(),
+        },
+        None => unreachable!(),
+    }
+}
+
+const fn float(src: &[u8], start: usize, end: usize) -> Json<'static> {
+    Json::Float(parse_f64(sub(src, start, end)))
+}
+
+/// Converts the number in `src[start..end]`, which must match the JSON number grammar.
+pub(crate) const fn number(src: &[u8], start: usize, end: usize) -> Json<'static> {
+    let mut idx = start;
+    while idx < end {
+        if matches!(src[idx], b'.' | b'e' | b'E') {
+            return float(src, start, end);
+        }
+        idx += 1;
+    }
+
+    // Accumulate negatively, so that `i64::MIN` fits
+    let neg = src[start] == b'-';
+    let mut int: i64 = 0;
+    let mut idx = start + neg as usize;
+    while idx < end {
+        int = match int.checked_mul(10) {
+            Some(int) => match int.checked_sub((src[idx] - b'0') as i64) {
+                Some(int) => int,
+                // Anything too large for an `i64` becomes a float, as in the runtime parser
+                None => return float(src, start, end),
+            },
+            None => return float(src, start, end),
+        };
+        idx += 1;
+    }
+    match neg {
+        true => Json::Int(int),
+        false => match int.checked_neg() {
+            Some(int) => Json::Int(int),
+            None => float(src, start, end),
+        },
+    }
+}
+
+/// A string in the source, between its quotes.
+#[derive(Clone, Copy)]
+struct StrToken {
+    start: usize,
+    end: usize,
+    /// The length once unescaped, if the string contains escapes.
+    unescaped: Option<usize>,
+}
+
+impl StrToken {
+    const fn unescaped_len(&self) -> usize {
+        match self.unescaped {
+            Some(len) => len,
+            None => 0,
+        }
+    }
+
+    /// Writes the decoded string to `buf` at `pos`, if it has escapes, returning the new end.
+    const fn unescape_into(&self, src: &[u8], buf: &mut [u8], mut pos: usize) -> usize {
+        if self.unescaped.is_none() {
+            return pos;
+        }
+        let mut idx = self.start;
+        while idx < self.end {
+            if src[idx] != b'\\' {
+                buf[pos] = src[idx];
+                pos += 1;
+                idx += 1;
+                continue;
+            }
+            let Ok((c, len)) = decode_escape(src, idx) else {
+                unreachable!();
+            };
+            let mut utf8 = [0; 4];
+            let utf8 = c.encode_utf8(&mut utf8).as_bytes();
+            let mut byte = 0;
+            while byte < utf8.len() {
+                buf[pos] = utf8[byte];
+                pos += 1;
+                byte += 1;
+            }
+            idx += len;
+        }
+        pos
+    }
+
+    /// Gets the string, from the source or from the decoded strings. `unescaped_pos` tracks where
+    /// the next decoded string starts.
+    const fn resolve(
+        &self,
+        src: &'static str,
+        unescaped: &'static str,
+        unescaped_pos: &mut usize,
+    ) -> &'static str {
+        match self.unescaped {
+            Some(len) => {
+                *unescaped_pos += len;
+                substr(unescaped, *unescaped_pos - len, *unescaped_pos)
+            }
+            None => substr(src, self.start, self.end),
+        }
+    }
+}
+
+/// Decodes the escape sequence whose backslash is at `bytes[at]`, returning the character and the
+/// length of the sequence. Mirrors the runtime parser's `decode_escape`.
+const fn decode_escape(bytes: &[u8], at: usize) -> Result<(char, usize), &'static str> {
+    if at + 1 >= bytes.len() {
+        return Err("unexpected end of input");
+    }
+    let c = match bytes[at + 1] {
+        b'"' => '"',
+        b'\\' => '\\',
+        b'/' => '/',
+        b'b' => '\u{8}',
+        b'f' => '\u{c}',
+        b'n' => '\n',
+        b'r' => '\r',
+        b't' => '\t',
+        b'u' => {
+            let Some(high) = hex4(bytes, at + 2) else {
+                return Err("invalid unicode escape");
+            };
+            if let 0xd800..=0xdbff = high {
+                let low =
+                    if at + 8 <= bytes.len() && bytes[at + 6] == b'\\' && bytes[at + 7] == b'u' {
+                        match hex4(bytes, at + 8) {
+                            Some(low) => Some(low),
+                            None => return Err("invalid unicode escape"),
+                        }
+                    } else {
+                        None
+                    };
+                return match low {
+                    Some(low @ 0xdc00..=0xdfff) => {
+                        match char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)) {
+                            Some(c) => Ok((c, 12)),
+                            None => Err("lone surrogate in unicode escape"),
+                        }
+                    }
+                    _ => Err("lone surrogate in unicode escape"),
+                };
+            }
+            return match char::from_u32(high) {
+                Some(c) => Ok((c, 6)),
+                None => Err("lone surrogate in unicode escape"),
+            };
+        }
+        _ => return Err("invalid escape sequence"),
+    };
+    Ok((c, 2))
+}
+
+const fn hex4(bytes: &[u8], at: usize) -> Option<u32> {
+    if at + 4 > bytes.len() {
+        return None;
+    }
+    let mut value = 0;
+    let mut idx = at;
+    while idx < at + 4 {
+        let digit = match bytes[idx] {
+            b @ b'0'..=b'9' => b - b'0',
+            b @ b'a'..=b'f' => b - b'a' + 10,
+            b @ b'A'..=b'F' => b - b'A' + 10,
+            _ => return None,
+        };
+        value = value * 16 + digit as u32;
+        idx += 1;
+    }
+    Some(value)
+}
+
+#[derive(Clone, Copy)]
+enum Token {
+    Null,
+    Bool(bool),
+    Number { start: usize, end: usize },
+    Str(StrToken),
+    Open { object: bool },
+}
+
+enum Event {
+    /// A value, with its key if it is in an object. `depth` is the number of containers it is in.
+    Item {
+        depth: usize,
+        key: Option<StrToken>,
+        token: Token,
+    },
+    /// The end of the container at `depth`.
+    Close {
+        depth: usize,
+        object: bool,
+    },
+    End,
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    /// Expecting the document's value.
+    Root,
+    /// Just inside a container.
+    First,
+    /// After a value.
+    Next,
+    Done,
+}
+
+/// A strict JSON parser that walks the document one value at a time, without recursion.
+struct Parser<'s> {
+    src: &'s [u8],
+    /// The file name used in error messages.
+    path: &'s str,
+    pos: usize,
+    /// Whether each open container is an object, outermost first.
+    stack: [bool; MAX_DEPTH],
+    depth: usize,
+    state: State,
+}
+
+impl<'s> Parser<'s> {
+    const fn new(src: &'s str, path: &'s str) -> Self {
+        Self {
+            src: src.as_bytes(),
+            path,
+            pos: 0,
+            stack: [false; MAX_DEPTH],
+            depth: 0,
+            state: State::Root,
+        }
+    }
+
+    const fn next(&mut self) -> Event {
+        match self.state {
+            State::Root => self.item(None),
+            State::First => {
+                self.skip_whitespace();
+                if self.at_close() {
+                    return self.close();
+                }
+                self.member()
+            }
+            State::Next => {
+                self.skip_whitespace();
+                if self.depth == 0 {
+                    if self.pos < self.src.len() {
+                        self.fail("trailing characters");
+                    }
+                    self.state = State::Done;
+                    return Event::End;
+                }
+                if self.at_close() {
+                    return self.close();
+                }
+                if !matches!(self.peek(), Some(b',')) {
+                    self.fail_unexpected();
+                }
+                self.pos += 1;
+                self.skip_whitespace();
+                self.member()
+            }
+            State::Done => Event::End,
+        }
+    }
+
+    const fn member(&mut self) -> Event {
+        if !self.stack[self.depth - 1] {
+            return self.item(None);
+        }
+        if !matches!(self.peek(), Some(b'"')) {
+            self.fail_unexpected();
+        }
+        let key = self.string();
+        self.skip_whitespace();
+        if !matches!(self.peek(), Some(b':')) {
+            self.fail_unexpected();
+        }
+        self.pos += 1;
+        self.item(Some(key))
+    }
+
+    const fn item(&mut self, key: Option<StrToken>) -> Event {
+        self.skip_whitespace();
+        let depth = self.depth;
+        let token = match self.peek() {
+            Some(b'n') => self.literal(b"null", Token::Null),
+            Some(b't') => self.literal(b"true", Token::Bool(true)),
+            Some(b'f') => self.literal(b"false", Token::Bool(false)),
+            Some(b'"') => Token::Str(self.string()),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b @ (b'[' | b'{')) => {
+                if self.depth == MAX_DEPTH {
+                    let mut msg = Message::new();
+                    msg.push(b"arrays and objects nested more than ");
+                    msg.push_number(MAX_DEPTH);
+                    msg.push(b" deep");
+                    self.fail(msg.as_str());
+                }
+                self.pos += 1;
+                self.stack[self.depth] = b == b'{';
+                self.depth += 1;
+                self.state = State::First;
+                return Event::Item {
+                    depth,
+                    key,
+                    token: Token::Open { object: b == b'{' },
+                };
+            }
+            _ => self.fail_unexpected(),
+        };
+        self.state = State::Next;
+        Event::Item { depth, key, token }
+    }
+
+    const fn at_close(&self) -> bool {
+        let close = if self.stack[self.depth - 1] {
+            b'}'
+        } else {
+            b']'
+        };
+        matches!(self.peek(), Some(b) if b == close)
+    }
+
+    const fn close(&mut self) -> Event {
+        self.pos += 1;
+        self.depth -= 1;
+        self.state = State::Next;
+        Event::Close {
+            depth: self.depth,
+            object: self.stack[self.depth],
+        }
+    }
+
+    const fn peek(&self) -> Option<u8> {
+        if self.pos < self.src.len() {
+            Some(self.src[self.pos])
+        } else {
+            None
+        }
+    }
+
+    // The loops below index directly rather than calling `peek`, since function calls are
+    // slow in a `const` context and these run for most bytes of the document
+
+    const fn skip_whitespace(&mut self) {
+        while self.pos < self.src.len()
+            && matches!(self.src[self.pos], b' ' | b'\t' | b'\n' | b'\r')
+        {
+            self.pos += 1;
+        }
+    }
+
+    const fn literal(&mut self, word: &[u8], token: Token) -> Token {
+        let mut idx = 0;
+        while idx < word.len() {
+            if !matches!(self.peek(), Some(b) if b == word[idx]) {
+                self.fail_unexpected();
+            }
+            self.pos += 1;
+            idx += 1;
+        }
+        token
+    }
+
+    const fn string(&mut self) -> StrToken {
+        self.pos += 1;
+        let start = self.pos;
+        let mut len = 0;
+        let mut escaped = false;
+        loop {
+            let run = self.pos;
+            while self.pos < self.src.len()
+                && !matches!(self.src[self.pos], b'"' | b'\\' | 0x00..=0x1f)
+            {
+                self.pos += 1;
+            }
+            len += self.pos - run;
+
+            match self.peek() {
+                None => self.fail("unexpected end of input"),
+                Some(b'"') => break,
+                Some(b'\\') => match decode_escape(self.src, self.pos) {
+                    Ok((c, escape_len)) => {
+                        escaped = true;
+                        len += c.len_utf8();
+                        self.pos += escape_len;
+                    }
+                    Err(msg) => self.fail(msg),
+                },
+                Some(_) => self.fail("unescaped control character in string"),
+            }
+        }
+        self.pos += 1;
+        StrToken {
+            start,
+            end: self.pos - 1,
+            unescaped: if escaped { Some(len) } else { None },
+        }
+    }
+
+    const fn number(&mut self) -> Token {
+        let start = self.pos;
+        if let Some(b'-') = self.peek() {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => {
+                self.pos += 1;
+                // A leading zero must be the whole integer part
+                if let Some(b'0'..=b'9') = self.peek() {
+                    self.fail("invalid number");
+                }
+            }
+            Some(b'1'..=b'9') => self.digits(),
+            _ => self.fail("invalid number"),
+        }
+        if let Some(b'.') = self.peek() {
+            self.pos += 1;
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                self.fail("invalid number");
+            }
+            self.digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                self.fail("invalid number");
+            }
+            self.digits();
+        }
+        Token::Number {
+            start,
+            end: self.pos,
+        }
+    }
+
+    const fn digits(&mut self) {
+        while self.pos < self.src.len() && self.src[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+    }
+
+    /// Fails on the character at the current position.
+    const fn fail_unexpected(&self) -> ! {
+        if self.pos >= self.src.len() {
+            self.fail("unexpected end of input");
+        }
+        let len = match self.src[self.pos] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        };
+        let mut msg = Message::new();
+        msg.push(b"unexpected '");
+        msg.push(sub(self.src, self.pos, self.pos + len));
+        msg.push(b"'");
+        self.fail(msg.as_str())
+    }
+
+    /// Fails compilation with `msg` and the current position, as `path:line:column: msg`.
+    const fn fail(&self, msg: &str) -> ! {
+        let (mut line, mut line_start) = (1, 0);
+        let mut idx = 0;
+        while idx < self.pos {
+            if self.src[idx] == b'\n' {
+                line += 1;
+                line_start = idx + 1;
+            }
+            idx += 1;
+        }
+        // Count characters rather than bytes, by skipping UTF-8 continuation bytes
+        let mut column = 1;
+        let mut idx = line_start;
+        while idx < self.pos {
+            if self.src[idx] & 0xc0 != 0x80 {
+                column += 1;
+            }
+            idx += 1;
+        }
+
+        let mut full = Message::new();
+        full.push(self.path.as_bytes());
+        full.push(b":");
+        full.push_number(line);
+        full.push(b":");
+        full.push_number(column);
+        full.push(b": ");
+        full.push(msg.as_bytes());
+        panic!("{}", full.as_str())
+    }
+}
+
+/// A fixed-capacity buffer for building error messages, since `const` panics cannot format
+/// numbers. Anything past the capacity is dropped.
+pub(crate) struct Message {
+    bytes: [u8; 256],
+    len: usize,
+}
+
+impl Message {
+    pub(crate) const fn new() -> Self {
+        Self {
+            bytes: [0; 256],
+            len: 0,
+        }
+    }
+
+    pub(crate) const fn push(&mut self, bytes: &[u8]) {
+        let mut idx = 0;
+        while idx < bytes.len() && self.len < self.bytes.len() {
+            self.bytes[self.len] = bytes[idx];
+            self.len += 1;
+            idx += 1;
+        }
+    }
+
+    pub(crate) const fn push_number(&mut self, mut n: usize) {
+        let mut digits = [0; 20];
+        let mut idx = digits.len();
+        loop {
+            idx -= 1;
+            digits[idx] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        self.push(sub(&digits, idx, digits.len()));
+    }
+
+    pub(crate) const fn as_str(&self) -> &str {
+        // Truncation can split a character, in which case the partial character is dropped
+        match str::from_utf8(sub(&self.bytes, 0, self.len)) {
+            Ok(s) => s,
+            Err(err) => match str::from_utf8(sub(&self.bytes, 0, err.valid_up_to())) {
+                Ok(s) => s,
+                Err(_) => "",
+            },
+        }
+    }
+}
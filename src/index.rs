@@ -0,0 +1,72 @@
+//! Indexing that reports failures instead of panicking.
+
+use crate::{Json, JsonError};
+
+/// A key that can look up a value in a [`Json`]: a `&str` for an object, or a `usize` for an
+/// array. Used by [`Json::try_index`].
+pub trait JsonKey<'k>: sealed::Sealed {
+    /// Looks up the value at this key in `json`.
+    fn get<'j, 'a>(self, json: &'j Json<'a>) -> Result<&'j Json<'a>, JsonError<'k>>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for &str {}
+    impl Sealed for usize {}
+}
+
+impl<'k> JsonKey<'k> for &'k str {
+    fn get<'j, 'a>(self, json: &'j Json<'a>) -> Result<&'j Json<'a>, JsonError<'k>> {
+        match json {
+            Json::Object(obj) => obj
+                .iter()
+                .find(|(k, _)| *k == self)
+                .map(|(_, v)| v)
+                .ok_or(JsonError::KeyNotFound { key: self }),
+            _ => Err(JsonError::WrongType {
+                expected: "object",
+                found: json.type_name(),
+            }),
+        }
+    }
+}
+
+impl JsonKey<'_> for usize {
+    fn get<'j, 'a>(self, json: &'j Json<'a>) -> Result<&'j Json<'a>, JsonError<'static>> {
+        match json {
+            Json::Array(arr) => arr.get(self).ok_or(JsonError::IndexOutOfRange {
+                index: self,
+                len: arr.len(),
+            }),
+            _ => Err(JsonError::WrongType {
+                expected: "array",
+                found: json.type_name(),
+            }),
+        }
+    }
+}
+
+impl Json<'_> {
+    /// Gets the value at a key of an object or an index of an array, like indexing with `[]`, but
+    /// returns an error instead of panicking.
+    ///
+    /// ```rust
+    /// use const_json::{Json, JsonError, const_json};
+    ///
+    /// const JSON: Json = const_json!({"list": [1, 2]});
+    ///
+    /// assert_eq!(JSON.try_index("list").unwrap().try_index(1), Ok(&Json::Int(2)));
+    /// assert_eq!(JSON.try_index("missing"), Err(JsonError::KeyNotFound { key: "missing" }));
+    /// assert_eq!(
+    ///     JSON["list"].try_index(5),
+    ///     Err(JsonError::IndexOutOfRange { index: 5, len: 2 }),
+    /// );
+    ///
+    /// let err = JSON.try_index(0).unwrap_err();
+    /// assert_eq!(err.to_string(), "expected array, found object");
+    /// ```
+    pub fn try_index<'k, K: JsonKey<'k>>(&self, key: K) -> Result<&Self, JsonError<'k>> {
+        key.get(self)
+    }
+}
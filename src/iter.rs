@@ -0,0 +1,215 @@
+//! Iteration over the elements of an array, and the values of an object.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+use core::slice;
+
+use crate::Json;
+
+/// An iterator over the elements of a [`Json::Array`], created by [`Json::iter`].
+///
+/// The length of an array is always known, so this iterates from either end and reports its exact
+/// remaining length.
+///
+/// ```rust
+/// use const_json::{Json, const_json};
+///
+/// const JSON: Json = const_json!([1, 2, 3, 2]);
+///
+/// let mut iter = JSON.iter();
+/// assert_eq!(iter.len(), 4);
+/// assert_eq!(iter.next_back(), Some(&Json::Int(2)));
+/// assert_eq!(iter.len(), 3);
+///
+/// // Searching from the back finds the last match
+/// let last = JSON.iter().rposition(|v| *v == Json::Int(2));
+/// assert_eq!(last, Some(3));
+///
+/// let reversed: Vec<i64> = JSON.iter().rev().map(Json::int).collect();
+/// assert_eq!(reversed, [2, 3, 2, 1]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Iter<'a>(slice::Iter<'a, Json<'a>>);
+
+impl<'a> Json<'a> {
+    /// Iterates over the elements of an array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not a [`Json::Array`]
+    pub fn iter(&self) -> Iter<'a> {
+        match *self {
+            Self::Array(arr) => Iter(arr.iter()),
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    /// Iterates over every run of `size` consecutive elements of an array, like
+    /// [`slice::windows`]. Anything other than a [`Json::Array`] gives an empty iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!([1, 2, 4, 8]);
+    ///
+    /// let rising = JSON.windows(2).all(|w| w[0].int() < w[1].int());
+    /// assert!(rising);
+    /// assert_eq!(JSON.windows(5).count(), 0);
+    /// assert_eq!(Json::Null(()).windows(1).count(), 0);
+    /// ```
+    pub fn windows(&self, size: usize) -> slice::Windows<'a, Json<'a>> {
+        self.elems().windows(size)
+    }
+
+    /// Iterates over an array in groups of `size` elements, the last of which may be shorter, like
+    /// [`slice::chunks`]. Anything other than a [`Json::Array`] gives an empty iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const PAIRS: Json = const_json!(["width", 80, "height", 24]);
+    ///
+    /// for pair in PAIRS.chunks(2) {
+    ///     assert!(pair[1].int() > 0, "{} must be positive", pair[0].str());
+    /// }
+    /// assert_eq!(PAIRS.chunks(3).last().unwrap().len(), 1);
+    /// ```
+    pub fn chunks(&self, size: usize) -> slice::Chunks<'a, Json<'a>> {
+        self.elems().chunks(size)
+    }
+
+    /// Iterates over every value stored at `key` in an object, in order. JSON allows a key to
+    /// appear more than once, and [`get_val`](Self::get_val) only finds the first. Anything other
+    /// than a [`Json::Object`] gives an empty iterator.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const HEADERS: Json = const_json!({"Set-Cookie": "a=1", "Host": "x", "Set-Cookie": "b=2"});
+    ///
+    /// let cookies: Vec<&str> = HEADERS.get_all("Set-Cookie").map(Json::str).collect();
+    /// assert_eq!(cookies, ["a=1", "b=2"]);
+    /// assert_eq!(HEADERS.get_all("Accept").count(), 0);
+    /// assert_eq!(Json::Null(()).get_all("Host").count(), 0);
+    /// ```
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &'a Json<'a>> {
+        let entries = match *self {
+            Self::Object(obj) => obj,
+            _ => &[],
+        };
+        entries
+            .iter()
+            .filter(move |(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn elems(&self) -> &'a [Json<'a>] {
+        match *self {
+            Self::Array(arr) => arr,
+            _ => &[],
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Json<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth(n)
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.0.nth_back(n)
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl FusedIterator for Iter<'_> {}
+
+/// The entries of an object, in insertion order or sorted by key, with ties kept in insertion
+/// order.
+pub(crate) struct Entries<'j, 'a> {
+    entries: &'j [(&'a str, Json<'a>)],
+    /// When sorting keys, the indices of `entries` in key order.
+    #[cfg(feature = "alloc")]
+    sorted: Option<Vec<usize>>,
+    /// Without `alloc`, [`Json::next_sorted_entry`] finds each next entry in turn instead.
+    #[cfg(not(feature = "alloc"))]
+    sort_keys: bool,
+    /// The index of the last entry yielded.
+    #[cfg(not(feature = "alloc"))]
+    prev: Option<usize>,
+    /// How many entries have been yielded so far.
+    yielded: usize,
+}
+
+impl<'j, 'a> Entries<'j, 'a> {
+    pub(crate) fn new(entries: &'j [(&'a str, Json<'a>)], sort_keys: bool) -> Self {
+        Self {
+            entries,
+            #[cfg(feature = "alloc")]
+            sorted: sort_keys.then(|| {
+                let mut sorted: Vec<usize> = (0..entries.len()).collect();
+                // A stable sort keeps repeated keys in their original order
+                sorted.sort_by(|&l, &r| Json::string_cmp(entries[l].0, entries[r].0));
+                sorted
+            }),
+            #[cfg(not(feature = "alloc"))]
+            sort_keys,
+            #[cfg(not(feature = "alloc"))]
+            prev: None,
+            yielded: 0,
+        }
+    }
+}
+
+impl<'j, 'a> Iterator for Entries<'j, 'a> {
+    type Item = &'j (&'a str, Json<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "alloc")]
+        let idx = match &self.sorted {
+            Some(sorted) => sorted.get(self.yielded).copied(),
+            None => Some(self.yielded).filter(|&idx| idx < self.entries.len()),
+        }?;
+        #[cfg(not(feature = "alloc"))]
+        let idx = match self.sort_keys {
+            true => Json::next_sorted_entry(self.entries, self.prev),
+            false => Some(self.yielded).filter(|&idx| idx < self.entries.len()),
+        }?;
+        self.yielded += 1;
+        #[cfg(not(feature = "alloc"))]
+        {
+            self.prev = Some(idx);
+        }
+        Some(&self.entries[idx])
+    }
+}
@@ -0,0 +1,303 @@
+//! Checks on the keys of objects.
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::fmt::{self, Write};
+
+use crate::Json;
+use crate::include::Message;
+#[cfg(feature = "alloc")]
+use crate::pointer::push_token;
+
+/// Compares two strings, treating ASCII letters as equal to their other case, in a `const`
+/// context.
+///
+/// Only `A`-`Z` fold to `a`-`z`. Every other byte must match exactly, so non-ASCII letters of
+/// different cases, such as `É` and `é`, are unequal, and no Unicode normalization is done.
+///
+/// ```rust
+/// use const_json::str_eq_ignore_ascii_case;
+///
+/// const _: () = assert!(str_eq_ignore_ascii_case("Content-Type", "content-type"));
+/// assert!(!str_eq_ignore_ascii_case("Café", "CAFÉ"));
+/// assert!(!str_eq_ignore_ascii_case("a", "ab"));
+/// ```
+pub const fn str_eq_ignore_ascii_case(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut idx = 0;
+    while idx < a.len() {
+        if !a[idx].eq_ignore_ascii_case(&b[idx]) {
+            return false;
+        }
+        idx += 1;
+    }
+    true
+}
+
+impl<'a> Json<'a> {
+    /// Gets a value stored at the given key, comparing keys with [`str_eq_ignore_ascii_case`].
+    /// If several keys match, the first is used.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const HEADERS: Json = const_json!({"Content-Type": "text/plain", "content-length": 5});
+    /// const TYPE: &str = HEADERS.get_val_ci("content-type").str();
+    ///
+    /// assert_eq!(TYPE, "text/plain");
+    /// assert_eq!(HEADERS.get_val_ci("Content-Length").int(), 5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    /// - `self` is not a [`Json::Object`]
+    /// - the key could not be found
+    pub const fn get_val_ci(&self, key: &str) -> &Self {
+        match self {
+            Self::Object(obj) => {
+                let mut idx = 0;
+                while idx < obj.len() {
+                    let (k, v) = &obj[idx];
+                    if str_eq_ignore_ascii_case(k, key) {
+                        return v;
+                    }
+                    idx += 1;
+                }
+                panic!("key not found");
+            }
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    /// The keys of `self` that are not keys of `other`, in order, each listed once. Anything other
+    /// than a [`Json::Object`] counts as having no keys.
+    ///
+    /// This is shallow: only the top-level keys are compared, not the keys of objects nested in
+    /// them.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const KNOWN: Json = const_json!({"host": "localhost", "port": 80, "tls": {"cert": null}});
+    /// const USER: Json = const_json!({"host": "example.com", "prot": 80, "tls": {"crt": "a"}});
+    ///
+    /// for key in USER.missing_keys(&KNOWN) {
+    ///     println!("your config uses `{key}`, which is no longer recognized");
+    /// }
+    /// assert_eq!(USER.missing_keys(&KNOWN), ["prot"]);
+    /// assert_eq!(KNOWN.missing_keys(&USER), ["port"]);
+    /// assert!(Json::Null(()).missing_keys(&KNOWN).is_empty());
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn missing_keys(&self, other: &Json<'_>) -> Vec<&'a str> {
+        let Self::Object(obj) = *self else {
+            return Vec::new();
+        };
+        let known: BTreeSet<&str> = match *other {
+            Json::Object(other) => other.iter().map(|&(key, _)| key).collect(),
+            _ => BTreeSet::new(),
+        };
+        let mut seen = BTreeSet::new();
+        obj.iter()
+            .map(|&(key, _)| key)
+            .filter(|key| !known.contains(key) && seen.insert(*key))
+            .collect()
+    }
+
+    /// The keys in `required` that are not keys of `self`, in order. Anything other than a
+    /// [`Json::Object`] counts as having no keys, so every required key is missing.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"host": "localhost"});
+    ///
+    /// let missing = CONFIG.missing_required(&["host", "port", "user"]);
+    /// assert_eq!(missing, ["port", "user"]);
+    /// assert_eq!(Json::Null(()).missing_required(&["host"]), ["host"]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn missing_required<'k>(&self, required: &[&'k str]) -> Vec<&'k str> {
+        required
+            .iter()
+            .copied()
+            .filter(|key| !self.has_key(key))
+            .collect()
+    }
+
+    /// Checks that every key in `required` is a key of `self`, in a `const` context, like
+    /// [`missing_required`](Self::missing_required).
+    ///
+    /// # Panics
+    ///
+    /// Panics, naming the first missing key, if any are missing. In a `const` this fails the
+    /// build:
+    ///
+    /// ```text
+    /// error[E0080]: evaluation panicked: missing required key "port"
+    /// ```
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"host": "localhost", "port": 80});
+    /// const _: () = CONFIG.assert_required(&["host", "port"]);
+    /// ```
+    pub const fn assert_required(&self, required: &[&str]) {
+        let mut idx = 0;
+        while idx < required.len() {
+            if !self.has_key(required[idx]) {
+                let mut msg = Message::new();
+                msg.push(b"missing required key \"");
+                msg.push(required[idx].as_bytes());
+                msg.push(b"\"");
+                panic!("{}", msg.as_str());
+            }
+            idx += 1;
+        }
+    }
+
+    /// Whether `self` is an object with an entry for `key`.
+    const fn has_key(&self, key: &str) -> bool {
+        let Self::Object(obj) = *self else {
+            return false;
+        };
+        let mut idx = 0;
+        while idx < obj.len() {
+            if Self::string_eq(obj[idx].0, key) {
+                return true;
+            }
+            idx += 1;
+        }
+        false
+    }
+
+    /// Finds the first object, in document order, with a key that appears more than once in it.
+    ///
+    /// JSON does not forbid repeated keys, but most readers keep only one of the values, so a
+    /// value with them usually means a mistake. This is the check that
+    /// [`Serializer::error_on_duplicate_keys`](crate::Serializer::error_on_duplicate_keys) makes.
+    /// Each object's keys are sorted rather than compared pairwise, and the search keeps its own
+    /// stack, so large and deeply nested values are fine.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"servers": [{"port": 80}, {"port": 81, "port": 82}]});
+    ///
+    /// let dup = CONFIG.find_duplicate_key().unwrap();
+    /// assert_eq!((dup.key, dup.pointer.as_str()), ("port", "/servers/1"));
+    /// assert_eq!(dup.to_string(), "duplicate key 'port' in object at '/servers/1'");
+    /// assert_eq!(const_json!({"a": {"a": 1}}).find_duplicate_key(), None);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn find_duplicate_key(&self) -> Option<DuplicateKey<'a>> {
+        let mut pointer = String::new();
+        let key = find_duplicate(self, &mut pointer)?;
+        Some(DuplicateKey { key, pointer })
+    }
+
+    /// The index of the first entry of `obj` whose key was already used by an earlier entry,
+    /// comparing every pair of keys, for when there is nowhere to sort them.
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) const fn duplicate_entry(obj: &[(&str, Self)]) -> Option<usize> {
+        let mut idx = 1;
+        while idx < obj.len() {
+            let mut prev = 0;
+            while prev < idx {
+                if Self::string_eq(obj[prev].0, obj[idx].0) {
+                    return Some(idx);
+                }
+                prev += 1;
+            }
+            idx += 1;
+        }
+        None
+    }
+}
+
+/// A key repeated within one object, found by [`Json::find_duplicate_key`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKey<'a> {
+    /// The repeated key.
+    pub key: &'a str,
+    /// A JSON Pointer to the object holding it.
+    pub pointer: String,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for DuplicateKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "duplicate key '{}' in object at '{}'",
+            self.key, self.pointer
+        )
+    }
+}
+
+/// Finds the first repeated key in `json`, leaving `pointer` pointing to its object if there is
+/// one.
+///
+/// The search keeps its own stack rather than recursing, so very deep values cannot overflow the
+/// call stack.
+#[cfg(feature = "alloc")]
+fn find_duplicate<'a>(json: &Json<'a>, pointer: &mut String) -> Option<&'a str> {
+    // Each container being searched, with the index of its next child and the length of
+    // `pointer` before that child's token
+    let mut stack: Vec<(&Json<'a>, usize, usize)> = Vec::new();
+    let mut next = Some(json);
+    loop {
+        if let Some(json) = next.take() {
+            match *json {
+                Json::Object(obj) => {
+                    if let Some(idx) = sorted_duplicate_entry(obj) {
+                        return Some(obj[idx].0);
+                    }
+                    stack.push((json, 0, pointer.len()));
+                }
+                Json::Array(_) => stack.push((json, 0, pointer.len())),
+                _ => {}
+            }
+        }
+        let (container, child, len) = stack.last_mut()?;
+        pointer.truncate(*len);
+        next = match **container {
+            Json::Array(arr) => arr.get(*child).inspect(|_| {
+                // Writing to a `String` cannot fail
+                let _ = write!(pointer, "/{child}");
+            }),
+            Json::Object(obj) => obj.get(*child).map(|(key, value)| {
+                push_token(pointer, key);
+                value
+            }),
+            _ => None,
+        };
+        match next {
+            Some(_) => *child += 1,
+            None => {
+                stack.pop();
+            }
+        }
+    }
+}
+
+/// The index of the first entry of `obj` whose key was already used by an earlier entry, found by
+/// sorting the keys.
+#[cfg(feature = "alloc")]
+pub(crate) fn sorted_duplicate_entry(obj: &[(&str, Json<'_>)]) -> Option<usize> {
+    let mut sorted: Vec<usize> = (0..obj.len()).collect();
+    // A stable sort puts each repeat of a key straight after the entry before it
+    sorted.sort_by(|&l, &r| obj[l].0.cmp(obj[r].0));
+    sorted
+        .windows(2)
+        .filter(|pair| obj[pair[0]].0 == obj[pair[1]].0)
+        .map(|pair| pair[1])
+        .min()
+}
@@ -3,8 +3,102 @@
 #![no_std]
 #![forbid(missing_docs, unsafe_code)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::cmp::Ordering;
 use core::ops::Index;
 
+#[cfg(feature = "alloc")]
+mod build;
+mod convert;
+#[cfg(feature = "alloc")]
+mod cow;
+mod error;
+mod escape;
+mod extract;
+#[cfg(feature = "alloc")]
+mod finite;
+#[cfg(feature = "alloc")]
+mod flatten;
+mod float;
+mod hash;
+mod include;
+mod index;
+mod iter;
+mod keys;
+#[cfg(feature = "alloc")]
+mod map;
+#[cfg(feature = "alloc")]
+mod merge;
+mod number;
+mod order;
+#[cfg(feature = "alloc")]
+mod owned;
+#[cfg(feature = "alloc")]
+mod parse;
+#[cfg(feature = "alloc")]
+mod patch;
+mod path;
+mod pointer;
+mod ser;
+#[cfg(feature = "serde_json")]
+mod serde_value;
+mod write;
+
+#[cfg(feature = "alloc")]
+pub use build::{ArrayBuilder, ObjectBuilder};
+#[cfg(feature = "alloc")]
+pub use cow::CowJson;
+pub use error::JsonError;
+pub use escape::{escape_into, escaped_len, needs_escaping};
+#[cfg(feature = "alloc")]
+pub use escape::{escape_str, unescape_str};
+pub use extract::PathError;
+#[cfg(feature = "alloc")]
+pub use finite::{FiniteJson, NonFiniteError};
+#[cfg(feature = "alloc")]
+pub use flatten::UnflattenError;
+pub use hash::HashableJson;
+pub use include::MAX_DEPTH as INCLUDE_MAX_DEPTH;
+pub use index::JsonKey;
+pub use iter::Iter;
+#[cfg(feature = "alloc")]
+pub use keys::DuplicateKey;
+pub use keys::str_eq_ignore_ascii_case;
+#[cfg(feature = "alloc")]
+pub use merge::{MergeStrategy, merge_all};
+pub use number::Number;
+pub use order::OrderedJson;
+#[cfg(feature = "alloc")]
+pub use owned::OwnedJson;
+#[cfg(feature = "alloc")]
+pub use parse::{
+    DuplicateKeyPolicy, ParseError, ParseErrorKind, ParserOptions, from_slice, minify, parse,
+    parse_borrowed, parse_json5, parse_jsonc, parse_many, parse_ndjson, parse_with,
+};
+#[cfg(feature = "alloc")]
+pub use patch::{
+    PatchError, PatchErrorKind, PatchOp, apply_merge_patch, apply_patch, merge_patch_diff,
+    patch_diff,
+};
+pub use pointer::{PointerError, PointerErrorKind};
+#[cfg(feature = "alloc")]
+pub use ser::to_string_pretty;
+pub use ser::{IndentStyle, NanPolicy, SerializeError, Serializer};
+
+// Used by `include_json`
+#[doc(hidden)]
+pub use include::{IncludeDoc, IncludeShape, include_doc, include_unescape};
+// Used by `json_get`
+#[doc(hidden)]
+pub use pointer::resolve_pointer;
+// Used by `json_path` and `try_json_path`
+#[doc(hidden)]
+pub use path::{PathSegment, PathValue};
+
 /// The result of a [`const_json`] macro call.
 #[derive(Clone, Copy, PartialEq, PartialOrd)]
 pub enum Json<'a> {
@@ -36,7 +130,326 @@ impl Json<'_> {
             }
             idx += 1;
         }
-        return true;
+        true
+    }
+
+    /// Compares strings by their bytes.
+    const fn string_cmp(l: &str, r: &str) -> Ordering {
+        let (l, r) = (l.as_bytes(), r.as_bytes());
+        let mut idx = 0;
+        while idx < l.len() && idx < r.len() {
+            if l[idx] != r[idx] {
+                return if l[idx] < r[idx] {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            idx += 1;
+        }
+        if l.len() < r.len() {
+            Ordering::Less
+        } else if l.len() > r.len() {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// Walks object entries in key order without allocating: given the index of the previous entry,
+    /// finds the one that comes next when sorted by key, with ties broken by position.
+    pub(crate) const fn next_sorted_entry(
+        obj: &[(&str, Json<'_>)],
+        prev: Option<usize>,
+    ) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        let mut idx = 0;
+        while idx < obj.len() {
+            let after_prev = match prev {
+                None => true,
+                Some(prev) => match Self::string_cmp(obj[idx].0, obj[prev].0) {
+                    Ordering::Less => false,
+                    Ordering::Equal => idx > prev,
+                    Ordering::Greater => true,
+                },
+            };
+            if after_prev {
+                best = match best {
+                    Some(best)
+                        if !matches!(Self::string_cmp(obj[idx].0, obj[best].0), Ordering::Less) =>
+                    {
+                        Some(best)
+                    }
+                    _ => Some(idx),
+                };
+            }
+            idx += 1;
+        }
+        best
+    }
+
+    /// Compares two values for deep structural equality, in a `const` context.
+    ///
+    /// The rules are:
+    /// - `Int` and `Float` compare numerically, so `Int(1)` equals `Float(1.0)`. The comparison is
+    ///   exact: an integer too large to be represented as an `f64` equals no float.
+    /// - `NaN` equals nothing, including itself, and `0.0` equals `-0.0`.
+    /// - Strings compare byte by byte.
+    /// - Arrays are equal if they have the same length and equal elements in the same order.
+    /// - Objects are equal if they have the same length and, position by position, equal keys and
+    ///   equal values. Entry order matters, and duplicate keys are compared like any other entry.
+    /// - Values of any other pair of variants are unequal.
+    ///
+    /// The derived [`PartialEq`] follows the same rules, except that it never considers an `Int`
+    /// equal to a `Float`.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const A: Json = const_json!({"n": 1, "list": [2.5, "x"]});
+    /// const B: Json = const_json!({"n": 1.0, "list": [2.5, "x"]});
+    /// const _: () = assert!(A.json_eq(&B));
+    /// assert_ne!(A, B);
+    ///
+    /// const SWAPPED: Json = const_json!({"list": [2.5, "x"], "n": 1});
+    /// assert!(!A.json_eq(&SWAPPED));
+    ///
+    /// assert!(!Json::Float(f64::NAN).json_eq(&Json::Float(f64::NAN)));
+    /// assert!(!Json::Int(i64::MAX).json_eq(&Json::Float(i64::MAX as f64)));
+    /// ```
+    pub const fn json_eq(&self, other: &Json<'_>) -> bool {
+        match (*self, *other) {
+            (Self::Null(()), Json::Null(())) => true,
+            (Self::Bool(l), Json::Bool(r)) => l == r,
+            (Self::Int(l), Json::Int(r)) => l == r,
+            (Self::Float(l), Json::Float(r)) => l == r,
+            (Self::Int(i), Json::Float(f)) | (Self::Float(f), Json::Int(i)) => {
+                Self::int_float_eq(i, f)
+            }
+            (Self::Str(l), Json::Str(r)) => Self::string_eq(l, r),
+            (Self::Array(l), Json::Array(r)) => {
+                if l.len() != r.len() {
+                    return false;
+                }
+                let mut idx = 0;
+                while idx < l.len() {
+                    if !l[idx].json_eq(&r[idx]) {
+                        return false;
+                    }
+                    idx += 1;
+                }
+                true
+            }
+            (Self::Object(l), Json::Object(r)) => {
+                if l.len() != r.len() {
+                    return false;
+                }
+                let mut idx = 0;
+                while idx < l.len() {
+                    if !Self::string_eq(l[idx].0, r[idx].0) || !l[idx].1.json_eq(&r[idx].1) {
+                        return false;
+                    }
+                    idx += 1;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `i` and `f` are exactly the same number.
+    const fn int_float_eq(i: i64, f: f64) -> bool {
+        // 2^63 is the first float past `i64::MAX`, which `i as f64` rounds up to
+        i as f64 == f && f < 9_223_372_036_854_775_808.0 && f as i64 == i
+    }
+
+    /// Compares two values like [`json_eq`](Self::json_eq), except that numbers are equal if they
+    /// differ by at most `epsilon`, in a `const` context. Use this to check a document built with
+    /// float arithmetic against an expected one.
+    ///
+    /// If `unordered` is set, objects are equal if they have the same entries in any order. A key
+    /// that appears more than once is matched by occurrence: its first entry in one object against
+    /// its first entry in the other, and so on.
+    ///
+    /// `NaN` still equals nothing, and infinities equal only themselves.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const COMPUTED: Json = const_json!({"total": (0.1 + 0.2), "parts": [1, {"ratio": 0.5}]});
+    /// const EXPECTED: Json = const_json!({"parts": [1.0, {"ratio": 0.5}], "total": 0.3});
+    ///
+    /// assert!(COMPUTED.deep_eq_tol(&EXPECTED, 1e-9, true));
+    /// assert!(!COMPUTED.deep_eq_tol(&EXPECTED, 1e-9, false));
+    /// assert!(!COMPUTED.deep_eq_tol(&EXPECTED, 0.0, true));
+    /// const _: () = assert!(Json::Int(10).deep_eq_tol(&Json::Int(12), 2.0, false));
+    /// ```
+    pub const fn deep_eq_tol(&self, other: &Json<'_>, epsilon: f64, unordered: bool) -> bool {
+        match (*self, *other) {
+            (Self::Null(()), Json::Null(())) => true,
+            (Self::Bool(l), Json::Bool(r)) => l == r,
+            (Self::Int(l), Json::Int(r)) => {
+                (l as i128 - r as i128).unsigned_abs() as f64 <= epsilon
+            }
+            (Self::Int(_) | Self::Float(_), Json::Int(_) | Json::Float(_)) => {
+                let (l, r) = (self.float(), other.float());
+                l == r || (l - r).abs() <= epsilon
+            }
+            (Self::Str(l), Json::Str(r)) => Self::string_eq(l, r),
+            (Self::Array(l), Json::Array(r)) => {
+                if l.len() != r.len() {
+                    return false;
+                }
+                let mut idx = 0;
+                while idx < l.len() {
+                    if !l[idx].deep_eq_tol(&r[idx], epsilon, unordered) {
+                        return false;
+                    }
+                    idx += 1;
+                }
+                true
+            }
+            (Self::Object(l), Json::Object(r)) => {
+                if l.len() != r.len() {
+                    return false;
+                }
+                let mut idx = 0;
+                while idx < l.len() {
+                    let (key, value) = &l[idx];
+                    let other = if unordered {
+                        Self::nth_entry(r, key, Self::occurrence(l, idx))
+                    } else if Self::string_eq(key, r[idx].0) {
+                        Some(&r[idx].1)
+                    } else {
+                        None
+                    };
+                    match other {
+                        Some(other) if value.deep_eq_tol(other, epsilon, unordered) => {}
+                        _ => return false,
+                    }
+                    idx += 1;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// How many entries before `idx` have the same key as it.
+    const fn occurrence(obj: &[(&str, Json<'_>)], idx: usize) -> usize {
+        let mut count = 0;
+        let mut before = 0;
+        while before < idx {
+            if Self::string_eq(obj[before].0, obj[idx].0) {
+                count += 1;
+            }
+            before += 1;
+        }
+        count
+    }
+
+    /// The value of the entry with the given key, skipping its first `n` occurrences.
+    const fn nth_entry<'j, 'a>(
+        obj: &'j [(&'a str, Json<'a>)],
+        key: &str,
+        mut n: usize,
+    ) -> Option<&'j Json<'a>> {
+        let mut idx = 0;
+        while idx < obj.len() {
+            if Self::string_eq(obj[idx].0, key) {
+                if n == 0 {
+                    return Some(&obj[idx].1);
+                }
+                n -= 1;
+            }
+            idx += 1;
+        }
+        None
+    }
+
+    /// Computes a 64-bit FNV-1a hash of `self`, in a `const` context.
+    ///
+    /// The hash is stable: it depends only on the value, not on the platform or the crate's build,
+    /// so it can be precomputed and embedded, for example as an ETag. Each value is hashed as a tag
+    /// byte for its variant followed by its contents:
+    /// - Floats are hashed by their bits, with `-0.0` treated as `0.0`. Distinct `NaN`s may hash
+    ///   differently.
+    /// - Strings and keys are hashed as their length followed by their bytes.
+    /// - Arrays are hashed as their length followed by their elements, in order.
+    /// - Objects are hashed as their length followed by their entries sorted by key, so reordering
+    ///   an object's entries does not change its hash.
+    ///
+    /// Values that are equal under the derived [`PartialEq`] hash the same, except for `NaN`s. An
+    /// `Int` and a `Float` never hash the same, even when [`json_eq`](Self::json_eq) considers them
+    /// equal.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"name": "app", "port": 8080});
+    /// const ETAG: u64 = CONFIG.fnv_hash();
+    ///
+    /// const REORDERED: Json = const_json!({"port": 8080, "name": "app"});
+    /// assert_eq!(REORDERED.fnv_hash(), ETAG);
+    /// const CHANGED: Json = const_json!({"name": "app", "port": 8081});
+    /// assert_ne!(CHANGED.fnv_hash(), ETAG);
+    ///
+    /// assert_eq!(Json::Float(-0.0).fnv_hash(), Json::Float(0.0).fnv_hash());
+    /// assert_ne!(Json::Int(1).fnv_hash(), Json::Float(1.0).fnv_hash());
+    /// ```
+    pub const fn fnv_hash(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        self.fnv_hash_into(OFFSET_BASIS)
+    }
+
+    const fn fnv_hash_into(&self, hash: u64) -> u64 {
+        match *self {
+            Self::Null(()) => Self::fnv_bytes(hash, &[0]),
+            Self::Bool(b) => Self::fnv_bytes(hash, &[1, b as u8]),
+            Self::Float(f) => {
+                let f = if f == 0.0 { 0.0 } else { f };
+                Self::fnv_bytes(Self::fnv_bytes(hash, &[2]), &f.to_bits().to_le_bytes())
+            }
+            Self::Int(i) => Self::fnv_bytes(Self::fnv_bytes(hash, &[3]), &i.to_le_bytes()),
+            Self::Str(s) => Self::fnv_str(Self::fnv_bytes(hash, &[4]), s),
+            Self::Array(arr) => {
+                let mut hash = Self::fnv_len(Self::fnv_bytes(hash, &[5]), arr.len());
+                let mut idx = 0;
+                while idx < arr.len() {
+                    hash = arr[idx].fnv_hash_into(hash);
+                    idx += 1;
+                }
+                hash
+            }
+            Self::Object(obj) => {
+                let mut hash = Self::fnv_len(Self::fnv_bytes(hash, &[6]), obj.len());
+                let mut next = Self::next_sorted_entry(obj, None);
+                while let Some(idx) = next {
+                    hash = obj[idx].1.fnv_hash_into(Self::fnv_str(hash, obj[idx].0));
+                    next = Self::next_sorted_entry(obj, next);
+                }
+                hash
+            }
+        }
+    }
+
+    const fn fnv_str(hash: u64, s: &str) -> u64 {
+        Self::fnv_bytes(Self::fnv_len(hash, s.len()), s.as_bytes())
+    }
+
+    const fn fnv_len(hash: u64, len: usize) -> u64 {
+        Self::fnv_bytes(hash, &(len as u64).to_le_bytes())
+    }
+
+    const fn fnv_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let mut idx = 0;
+        while idx < bytes.len() {
+            hash ^= bytes[idx] as u64;
+            hash = hash.wrapping_mul(PRIME);
+            idx += 1;
+        }
+        hash
     }
 
     /// Gets a value stored at the given key.
@@ -63,6 +476,41 @@ impl Json<'_> {
         }
     }
 
+    /// Gets the value of the first of `keys` that the object has, trying them in the order given,
+    /// for fields that have been renamed or are spelled more than one way. Returns `None` if none of
+    /// them are present, or `self` is not a [`Json::Object`].
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const OLD: Json = const_json!({"colour": "red"});
+    /// const NEW: Json = const_json!({"color": "blue", "colour": "red"});
+    /// const COLOR: &str = OLD.get_any(&["color", "colour"]).unwrap().str();
+    ///
+    /// assert_eq!(COLOR, "red");
+    /// assert_eq!(NEW.get_any(&["color", "colour"]).unwrap().str(), "blue");
+    /// assert_eq!(NEW.get_any(&["hue"]), None);
+    /// assert_eq!(Json::Null(()).get_any(&["color"]), None);
+    /// ```
+    pub const fn get_any(&self, keys: &[&str]) -> Option<&Self> {
+        let Self::Object(obj) = self else {
+            return None;
+        };
+        let mut k = 0;
+        while k < keys.len() {
+            let mut i = 0;
+            while i < obj.len() {
+                let (key, v) = &obj[i];
+                if Self::string_eq(key, keys[k]) {
+                    return Some(v);
+                }
+                i += 1;
+            }
+            k += 1;
+        }
+        None
+    }
+
     /// Gets a value stored at the given index.
     ///
     /// # Panics
@@ -77,12 +525,130 @@ impl Json<'_> {
         }
     }
 
+    /// Gets an element of an array, counting from the end if `index` is negative, so `-1` is the
+    /// last element. Returns `None` if `self` is not a [`Json::Array`] or the index is out of
+    /// range.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!([1, 2, 3]);
+    /// const LAST: i64 = JSON.get_rel(-1).unwrap().int();
+    ///
+    /// assert_eq!(LAST, 3);
+    /// assert_eq!(JSON.get_rel(0), Some(&Json::Int(1)));
+    /// assert_eq!(JSON.get_rel(-3), Some(&Json::Int(1)));
+    /// assert_eq!(JSON.get_rel(-4), None);
+    /// assert_eq!(JSON.get_rel(3), None);
+    /// ```
+    pub const fn get_rel(&self, index: i64) -> Option<&Self> {
+        match self {
+            Self::Array(arr) => {
+                let len = arr.len() as i64;
+                let index = if index < 0 { index + len } else { index };
+                if index >= 0 && index < len {
+                    Some(&arr[index as usize])
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the key of the `index`-th entry of an object, in insertion order, in a `const`
+    /// context, where iterators cannot be used. Returns `None` if `self` is not a
+    /// [`Json::Object`] or the index is out of range.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const LIMITS: Json = const_json!({"cpu": 2, "memory": 512, "disk": 10});
+    /// const NAMES: [&str; 3] = {
+    ///     let mut names = [""; 3];
+    ///     let mut idx = 0;
+    ///     while let Some(key) = LIMITS.key_at(idx) {
+    ///         names[idx] = key;
+    ///         idx += 1;
+    ///     }
+    ///     names
+    /// };
+    ///
+    /// assert_eq!(NAMES, ["cpu", "memory", "disk"]);
+    /// assert_eq!(LIMITS.key_at(3), None);
+    /// assert_eq!(const_json!(["cpu"]).key_at(0), None);
+    /// ```
+    pub const fn key_at(&self, index: usize) -> Option<&str> {
+        match self {
+            Self::Object(obj) if index < obj.len() => Some(obj[index].0),
+            _ => None,
+        }
+    }
+
+    /// Gets the value of the `index`-th entry of an object, in insertion order, in a `const`
+    /// context, to go with [`key_at`](Self::key_at). Returns `None` if `self` is not a
+    /// [`Json::Object`] or the index is out of range.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const LIMITS: Json = const_json!({"cpu": 2, "memory": 512, "disk": 10});
+    /// const TOTAL: i64 = {
+    ///     let mut total = 0;
+    ///     let mut idx = 0;
+    ///     while let Some(value) = LIMITS.value_at(idx) {
+    ///         total += value.int();
+    ///         idx += 1;
+    ///     }
+    ///     total
+    /// };
+    ///
+    /// assert_eq!(TOTAL, 524);
+    /// assert_eq!(LIMITS.value_at(1), Some(&Json::Int(512)));
+    /// assert_eq!(LIMITS.value_at(3), None);
+    /// ```
+    pub const fn value_at(&self, index: usize) -> Option<&Self> {
+        match self {
+            Self::Object(obj) if index < obj.len() => Some(&obj[index].1),
+            _ => None,
+        }
+    }
+
+    /// The index of the first entry of an object with the given key, in insertion order, the
+    /// inverse of [`key_at`](Self::key_at). Returns `None` if `self` is not a [`Json::Object`] or
+    /// the key could not be found.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const COLUMNS: Json = const_json!({"id": "int", "name": "text", "id": "uuid"});
+    /// const NAME: usize = COLUMNS.position_of("name").unwrap();
+    ///
+    /// assert_eq!(NAME, 1);
+    /// assert_eq!(COLUMNS.position_of("id"), Some(0));
+    /// assert_eq!(COLUMNS.position_of("email"), None);
+    /// assert_eq!(const_json!(["id"]).position_of("id"), None);
+    /// ```
+    pub const fn position_of(&self, key: &str) -> Option<usize> {
+        let Self::Object(obj) = *self else {
+            return None;
+        };
+        let mut idx = 0;
+        while idx < obj.len() {
+            if Self::string_eq(obj[idx].0, key) {
+                return Some(idx);
+            }
+            idx += 1;
+        }
+        None
+    }
+
     /// Unwraps a Null value.
     ///
     /// # Panics
     ///
     /// Panics if `self` is not a [`Json::Null`]
-    pub const fn null(&self) -> () {
+    pub const fn null(&self) {
         match *self {
             Self::Null(inner) => inner,
             _ => panic!("wrong variant"),
@@ -101,16 +667,48 @@ impl Json<'_> {
         }
     }
 
-    /// Unwraps a Float value.
+    /// Converts any value to a `bool`, loosely, for data such as environment-style settings where
+    /// booleans arrive as strings. Unlike [`bool`](Self::bool), this never panics.
+    ///
+    /// The rules are:
+    /// - `true` and `false` are themselves
+    /// - `null` is false
+    /// - numbers are true unless they are zero or `NaN`
+    /// - the strings `"false"`, `"0"` and `""` are false, and every other string is true, including
+    ///   `"true"`, `"1"` and `"no"`. Comparisons are exact, so `"False"` is true
+    /// - arrays and objects are true, even if they are empty
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUES: Json = const_json!(["true", "1", "yes", 1, 0.5, [], "false", "0", "", 0, null]);
+    /// const _: () = assert!(VALUES.get_idx(0).to_bool_loose());
+    ///
+    /// let loose: Vec<bool> = VALUES.iter().map(Json::to_bool_loose).collect();
+    /// assert_eq!(loose, [true, true, true, true, true, true, false, false, false, false, false]);
+    /// ```
+    pub const fn to_bool_loose(&self) -> bool {
+        match *self {
+            Self::Null(()) => false,
+            Self::Bool(b) => b,
+            Self::Int(i) => i != 0,
+            Self::Float(f) => f != 0.0 && !f.is_nan(),
+            Self::Str(s) => {
+                !(s.is_empty() || Self::string_eq(s, "false") || Self::string_eq(s, "0"))
+            }
+            Self::Array(_) | Self::Object(_) => true,
+        }
+    }
+
+    /// Unwraps a Float value, converting an Int value if necessary.
     ///
     /// # Panics
     ///
-    /// Panics if `self` is not a [`Json::Float`]
+    /// Panics if `self` is not a [`Json::Float`] or a [`Json::Int`]
     pub const fn float(&self) -> f64 {
-        match *self {
-            Self::Float(inner) => inner,
-            Self::Int(inner) => inner as f64,
-            _ => panic!("wrong variant"),
+        match self.number() {
+            Some(number) => number.as_f64(),
+            None => panic!("wrong variant"),
         }
     }
 
@@ -126,6 +724,54 @@ impl Json<'_> {
         }
     }
 
+    /// Gets a non-negative integer that fits in a `usize`, such as a length or an index. Floats
+    /// with no fractional part are accepted too. Returns `None` for anything else, including
+    /// integers too large for a `usize` on the current target.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!({"count": 4, "ratio": 0.5, "offset": (-1), "size": 2.0});
+    /// const BUF: [u8; JSON.get_val("count").try_as_usize().unwrap()] = [0; 4];
+    ///
+    /// assert_eq!(BUF.len(), 4);
+    /// assert_eq!(JSON["size"].try_as_usize(), Some(2));
+    /// assert_eq!(JSON["ratio"].try_as_usize(), None);
+    /// assert_eq!(JSON["offset"].try_as_usize(), None);
+    /// ```
+    pub const fn try_as_usize(&self) -> Option<usize> {
+        match *self {
+            Self::Int(i) if i >= 0 && i as u64 <= usize::MAX as u64 => Some(i as usize),
+            // Adding one rounds to 2^64 on 64-bit targets, and is exact on smaller ones
+            Self::Float(f) if f >= 0.0 && f < usize::MAX as f64 + 1.0 && f as usize as f64 == f => {
+                Some(f as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads any number as an `f64`, whether it was written as an integer or a float, in a `const`
+    /// context. Integers too large to be exact round to the nearest `f64`. Returns `None` if
+    /// `self` is not a number.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!({"timeout": 5, "ratio": 0.5, "name": "5"});
+    /// const TIMEOUT: f64 = JSON.get_val("timeout").as_number_f64().unwrap();
+    ///
+    /// assert_eq!(TIMEOUT, 5.0);
+    /// assert_eq!(JSON["ratio"].as_number_f64(), Some(0.5));
+    /// assert_eq!(JSON["name"].as_number_f64(), None);
+    /// ```
+    pub const fn as_number_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Int(i) => Some(i as f64),
+            Self::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
     /// Unwraps a Str value.
     ///
     /// # Panics
@@ -139,6 +785,72 @@ impl Json<'_> {
     }
 }
 
+/// Whether `a` and `b` hold the same data, however it was written, in a `const` context. Use this
+/// to check a parsed document against a [`const_json`] literal.
+///
+/// Formatting such as whitespace never reaches a [`Json`] value, so this only has to look past
+/// differences in how the data is represented. It ignores:
+/// - whether a number is an `Int` or a `Float`, as long as the two are exactly the same number,
+///   as in [`Json::json_eq`]
+/// - the order of the entries of an object. A key that appears more than once is matched by
+///   occurrence: its first entry in `a` against its first entry in `b`, and so on
+/// - everything about strings except their contents, such as which escapes the source used
+///
+/// Everything else must match: the order of array elements, the number of times each key
+/// appears, and the strings byte by byte. `NaN` equals nothing, and `0.0` equals `-0.0`.
+///
+/// ```rust
+/// use const_json::{Json, const_json, include_json, json_equivalent};
+///
+/// const PARSED: Json = include_json!("../examples/config.json");
+/// const LIMITS: Json = const_json!({
+///     "huge": 18446744073709551616.0,
+///     "timeout": 1500,
+///     "ratio": 0.75,
+///     "connections": 64
+/// });
+/// const _: () = assert!(json_equivalent(PARSED.get_val("limits"), &LIMITS));
+/// assert_ne!(PARSED["limits"], LIMITS);
+///
+/// assert!(json_equivalent(&PARSED["matrix"], &const_json!([[1.0, 2], [3, 4], []])));
+/// assert!(!json_equivalent(&PARSED["matrix"], &const_json!([[3, 4], [1, 2], []])));
+/// assert!(!json_equivalent(&const_json!({"a": 1}), &const_json!({"a": 1, "a": 1})));
+/// ```
+pub const fn json_equivalent(a: &Json<'_>, b: &Json<'_>) -> bool {
+    match (*a, *b) {
+        (Json::Int(_) | Json::Float(_), Json::Int(_) | Json::Float(_)) => a.json_eq(b),
+        (Json::Array(l), Json::Array(r)) => {
+            if l.len() != r.len() {
+                return false;
+            }
+            let mut idx = 0;
+            while idx < l.len() {
+                if !json_equivalent(&l[idx], &r[idx]) {
+                    return false;
+                }
+                idx += 1;
+            }
+            true
+        }
+        (Json::Object(l), Json::Object(r)) => {
+            if l.len() != r.len() {
+                return false;
+            }
+            let mut idx = 0;
+            while idx < l.len() {
+                let (key, value) = &l[idx];
+                match Json::nth_entry(r, key, Json::occurrence(l, idx)) {
+                    Some(other) if json_equivalent(value, other) => {}
+                    _ => return false,
+                }
+                idx += 1;
+            }
+            true
+        }
+        _ => a.json_eq(b),
+    }
+}
+
 /// Declares a borrowed JSON structure without allocation at compile time. Valid JSON syntax works,
 /// as well as one-token Rust expressions (such as a variable name). If you want to embed a more
 /// complex expression, just surround it with parentheses.
@@ -164,17 +876,252 @@ impl Json<'_> {
 ///
 /// const VARIABLE: i64 = 10;
 /// ```
+///
+/// A key can also be computed, by writing an expression in square brackets or parentheses in
+/// place of the string literal, as in JavaScript. The expression must evaluate to a `&str`.
+/// ```rust
+/// use const_json::{Json, const_json};
+///
+/// const ID: &str = "id";
+/// const NAMES: [&str; 2] = ["first", "last"];
+///
+/// const USER: Json = const_json!({[ID]: 7, "role": "admin", [NAMES[0]]: "Ada", (NAMES[1]): "L"});
+/// assert_eq!(USER, const_json!({"id": 7, "role": "admin", "first": "Ada", "last": "L"}));
+/// ```
+///
+/// An object can start with one or more spreads of other objects, written `..NAME`, followed by
+/// any keys of its own. The spreads are merged from left to right, then the keys, so later ones
+/// win. A key keeps the position where it first appears, with the value from where it last
+/// appears, and appears only once. The merge happens at compile time, so the spread objects and
+/// every value in an object with spreads must be constants.
+/// ```rust
+/// use const_json::{Json, const_json};
+///
+/// const DEFAULTS: Json = const_json!({"host": "localhost", "port": 80, "tls": false});
+/// const SITE: Json = const_json!({"port": 8080, "root": "/srv"});
+/// const LOCAL: Json = const_json!({"tls": true, "port": 8443});
+///
+/// const CONFIG: Json = const_json!({..DEFAULTS, ..SITE, ..LOCAL, "root": "/tmp", "debug": true});
+/// assert_eq!(CONFIG, const_json!({
+///     "host": "localhost",
+///     "port": 8443,
+///     "tls": true,
+///     "root": "/tmp",
+///     "debug": true
+/// }));
+/// ```
 #[macro_export]
 macro_rules! const_json {
     (null) => {$crate::Json::Null(())};
 
     ([$($json:tt),*]) => {$crate::Json::Array(&[$($crate::const_json!($json)),*])};
-    ({$($key:literal: $val:tt),*}) => {
-        $crate::Json::Object(&[$(($key, $crate::const_json!($val))),*])
+    ({$($key:tt: $val:tt),*}) => {
+        $crate::Json::Object(&[$(($crate::const_json!(@key $key), $crate::const_json!($val))),*])
+    };
+    ({.. $base:tt $(, $($rest:tt)*)?}) => {$crate::const_json!(@spread [$base] $($($rest)*)?)};
+    (@spread [$($base:tt)*] .. $next:tt $(, $($rest:tt)*)?) => {
+        $crate::const_json!(@spread [$($base)* $next] $($($rest)*)?)
     };
+    (@spread [$($base:tt)*] $($key:tt: $val:tt),*) => {{
+        const OBJECTS: &[$crate::Json<'static>] = &[
+            $($crate::const_json!($base),)*
+            $crate::const_json!({$($key: $val),*}),
+        ];
+        const MERGED: (
+            [(&str, $crate::Json<'static>); $crate::ObjectSpread::capacity(OBJECTS)],
+            usize,
+        ) = $crate::ObjectSpread::merge(OBJECTS);
+        $crate::Json::Object((&MERGED.0).split_at(MERGED.1).0)
+    }};
+    (@key $key:literal) => {$key};
+    (@key [$key:expr]) => {$key};
+    (@key ($key:expr)) => {$key};
     ($expr:expr) => {$crate::JsonSmuggler::new($expr).to_json()};
 }
 
+/// Embeds a JSON file as a [`Json`] constant, parsing it at compile time. The path is resolved like
+/// [`include_str`]'s, relative to the file the macro is called from.
+///
+/// The file must be strict JSON, with arrays and objects nested at most [`INCLUDE_MAX_DEPTH`] (128)
+/// deep. Anything else fails the build, with an error giving the file and the line and column of
+/// the problem:
+///
+/// ```text
+/// error[E0080]: evaluation panicked: config.json:3:14: unexpected '}'
+/// error[E0080]: evaluation panicked: too_deep.json:1:129: arrays and objects nested more than 128 deep
+/// ```
+///
+/// ```rust,compile_fail,E0080
+/// // 129 arrays inside each other
+/// const DEEP: const_json::Json = const_json::include_json!("../examples/too_deep.json");
+/// ```
+///
+/// Numbers convert exactly as they would with the runtime parser, including integers too large for
+/// an `i64` becoming floats. Parsing happens during `const` evaluation, which is far slower than
+/// running code, so this suits configuration-sized files: tens of kilobytes add a few seconds to a
+/// build.
+/// ```rust
+/// use const_json::{Json, include_json};
+///
+/// const CONFIG: Json = include_json!("../examples/config.json");
+///
+/// assert_eq!(CONFIG["name"].str(), "example");
+/// assert_eq!(CONFIG["limits"]["ratio"].float(), 0.75);
+/// assert_eq!(CONFIG["greeting"].str(), "Hello, \"world\" 😀");
+/// const MAX_CONNECTIONS: i64 = CONFIG.get_val("limits").get_val("connections").int();
+/// assert_eq!(MAX_CONNECTIONS, 64);
+///
+/// // The same as parsing the file at runtime
+/// # #[cfg(feature = "alloc")]
+/// assert_eq!(const_json::parse(include_str!("../examples/config.json")).unwrap(), CONFIG);
+/// ```
+#[macro_export]
+macro_rules! include_json {
+    ($path:literal) => {{
+        const SRC: &str = include_str!($path);
+        // Each pass over a large file can take longer than the lint expects
+        #[allow(long_running_const_eval)]
+        const SHAPE: $crate::IncludeShape = $crate::IncludeShape::of(SRC, $path);
+        #[allow(long_running_const_eval)]
+        static UNESCAPED: [u8; SHAPE.unescaped] = $crate::include_unescape(SRC);
+        // A static rather than a const, so that it can refer to itself
+        #[allow(long_running_const_eval)]
+        static DOC: $crate::IncludeDoc<{ SHAPE.elems }, { SHAPE.entries }> =
+            $crate::include_doc(SRC, &UNESCAPED, &SHAPE, &DOC);
+        DOC.0[0]
+    }};
+}
+
+/// Declares several named [`Json`] constants at once, each written as `NAME => json`, where the
+/// JSON is anything accepted by [`const_json`]. Entries may have doc comments, other attributes and
+/// a visibility.
+/// ```rust
+/// use const_json::json_consts;
+///
+/// json_consts! {
+///     /// The settings used in development.
+///     pub DEV => {"host": "localhost", "port": 8080},
+///     /// The settings used in production.
+///     pub(crate) PROD => {"host": "example.com", "port": 443},
+///     PORTS => [8080, 443],
+/// }
+///
+/// assert_eq!(DEV["port"].int(), 8080);
+/// assert_eq!(PROD["host"].str(), "example.com");
+/// const PORT: i64 = PORTS.get_idx(1).int();
+/// assert_eq!(PORT, 443);
+/// ```
+#[macro_export]
+macro_rules! json_consts {
+    ($($(#[$meta:meta])* $vis:vis $name:ident => $json:tt),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            $vis const $name: $crate::Json<'static> = $crate::const_json!($json);
+        )*
+    };
+}
+
+/// Gets the value at a JSON Pointer in a constant [`Json`], like [`Json::pointer`], checking the
+/// pointer at compile time.
+///
+/// The lookup happens in a `const` block, so the first argument must be a constant, such as a
+/// `const` item, and the pointer must be a constant `&str`. If the pointer is malformed, or does
+/// not lead to a value, the build fails with the reason and the pointer:
+///
+/// ```text
+/// error[E0080]: evaluation panicked: index 'x' is not a valid array index, in pointer '/servers/x'
+/// ```
+///
+/// ```rust
+/// use const_json::{Json, const_json, json_get};
+///
+/// const CONFIG: Json = const_json!({"servers": [{"host": "localhost"}], "a/b": 1});
+///
+/// let host = json_get!(CONFIG, "/servers/0/host");
+/// assert_eq!(host.str(), "localhost");
+/// assert_eq!(json_get!(CONFIG, "/a~1b").int(), 1);
+/// ```
+///
+/// ```rust,compile_fail
+/// use const_json::{Json, const_json, json_get};
+///
+/// const CONFIG: Json = const_json!({"servers": [{"host": "localhost"}]});
+/// let host = json_get!(CONFIG, "/servers/x/host");
+/// ```
+#[macro_export]
+macro_rules! json_get {
+    ($json:expr, $ptr:expr $(,)?) => {
+        const { $crate::resolve_pointer(&$json, $ptr) }
+    };
+}
+
+/// Reads a typed value from a nested path, as in `json_path!(JSON, int, "servers", 0, "port")`.
+///
+/// The first argument is the [`Json`] to start from, and the second selects what to read at the
+/// end of the path: `null`, `bool`, `float`, `int` or `str`, matching the accessors of the same
+/// names, or `json` for the [`Json`] value itself. Each segment after that is a `&str` key into an
+/// object or a `usize` index into an array. Segments can be any expression of those types, not
+/// just literals.
+///
+/// Works in a `const` context. Like the accessors it expands to, it panics if a segment is
+/// missing or the value has the wrong variant. See [`try_json_path`] for a version returning an
+/// `Option`.
+/// ```rust
+/// use const_json::{Json, const_json, json_path};
+///
+/// const CONFIG: Json = const_json!({
+///     "servers": [{"host": "localhost", "port": 8080}],
+///     "retries": 3
+/// });
+///
+/// const PORT: i64 = json_path!(CONFIG, int, "servers", 0, "port");
+/// assert_eq!(PORT, 8080);
+///
+/// const HOST: &str = json_path!(CONFIG, str, "servers", 0, "host");
+/// assert_eq!(HOST, "localhost");
+/// assert_eq!(json_path!(CONFIG, float, "retries"), 3.0);
+/// assert_eq!(json_path!(CONFIG, json, "servers", 0)["port"].int(), 8080);
+/// ```
+#[macro_export]
+macro_rules! json_path {
+    ($json:expr, $ty:ident $(, $seg:expr)* $(,)?) => {{
+        let json: &$crate::Json = &$json;
+        $(let json = $crate::PathSegment($seg).get(json);)*
+        $crate::PathValue::$ty(json).expect("wrong variant")
+    }};
+}
+
+/// Like [`json_path`], but gives `None` instead of panicking if a segment is missing, a segment
+/// is applied to the wrong variant, or the value has the wrong variant.
+/// ```rust
+/// use const_json::{Json, const_json, try_json_path};
+///
+/// const CONFIG: Json = const_json!({"servers": [{"port": 8080}]});
+///
+/// const PORT: Option<i64> = try_json_path!(CONFIG, int, "servers", 0, "port");
+/// assert_eq!(PORT, Some(8080));
+///
+/// assert_eq!(try_json_path!(CONFIG, int, "servers", 1, "port"), None);
+/// assert_eq!(try_json_path!(CONFIG, str, "servers", 0, "port"), None);
+/// assert_eq!(try_json_path!(CONFIG, int, 0), None);
+/// ```
+#[macro_export]
+macro_rules! try_json_path {
+    ($json:expr, $ty:ident $(, $seg:expr)* $(,)?) => {{
+        let json: ::core::option::Option<&$crate::Json> = ::core::option::Option::Some(&$json);
+        $(
+            let json = match json {
+                ::core::option::Option::Some(json) => $crate::PathSegment($seg).try_get(json),
+                ::core::option::Option::None => ::core::option::Option::None,
+            };
+        )*
+        match json {
+            ::core::option::Option::Some(json) => $crate::PathValue::$ty(json),
+            ::core::option::Option::None => ::core::option::Option::None,
+        }
+    }};
+}
+
 // Used for automatic type inference
 #[doc(hidden)]
 pub struct JsonSmuggler<T>(T);
@@ -207,6 +1154,57 @@ impl<'a> JsonSmuggler<Json<'a>> {
     }
 }
 
+// Used for the object spreads of `const_json`
+#[doc(hidden)]
+pub struct ObjectSpread;
+
+impl ObjectSpread {
+    // The most entries that merging `objects` can give, if no keys overlap
+    pub const fn capacity(objects: &[Json<'_>]) -> usize {
+        let mut len = 0;
+        let mut idx = 0;
+        while idx < objects.len() {
+            match objects[idx] {
+                Json::Object(obj) => len += obj.len(),
+                _ => panic!("only objects can be spread"),
+            }
+            idx += 1;
+        }
+        len
+    }
+
+    // Merges `objects` from left to right. A key keeps the position where it first appears,
+    // and the value from where it last appears. The entries are the first `len` of the array.
+    pub const fn merge<'a, const N: usize>(
+        objects: &[Json<'a>],
+    ) -> ([(&'a str, Json<'a>); N], usize) {
+        let mut entries = [("", Json::Null(())); N];
+        let mut len = 0;
+        let mut idx = 0;
+        while idx < objects.len() {
+            let Json::Object(obj) = objects[idx] else {
+                panic!("only objects can be spread");
+            };
+            let mut entry = 0;
+            while entry < obj.len() {
+                let (key, value) = obj[entry];
+                let mut pos = 0;
+                while pos < len && !Json::string_eq(entries[pos].0, key) {
+                    pos += 1;
+                }
+                if pos == len {
+                    entries[len].0 = key;
+                    len += 1;
+                }
+                entries[pos].1 = value;
+                entry += 1;
+            }
+            idx += 1;
+        }
+        (entries, len)
+    }
+}
+
 impl<'a> Index<usize> for Json<'a> {
     type Output = Json<'a>;
 
@@ -223,30 +1221,84 @@ impl<'a> Index<&'a str> for Json<'a> {
     }
 }
 
+/// Writes `[1, 2]` for arrays and `{ "a": 1 }` for objects, with strings as Rust string literals
+/// and floats as by their [`Display`](core::fmt::Display) impl. An explicit stack is used rather
+/// than recursion, so very deep values cannot overflow the call stack. Without the `alloc`
+/// feature it has room for [`Serializer::MAX_DEPTH`] levels, and deeper arrays and objects are
+/// written as `[..]` and `{..}`.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{Json, const_json};
+///
+/// const VALUE: Json = const_json!({"a": [1, 2.5, "x"], "b": {}});
+/// assert_eq!(format!("{VALUE:?}"), r#"{ "a": [1, 2.5, "x"], "b": { } }"#);
+///
+/// let mut deep = Json::Null(());
+/// for _ in 0..100_000 {
+///     deep = Json::Array(Box::leak(Box::new([deep])));
+/// }
+/// assert_eq!(format!("{deep:?}").len(), 200_004);
+/// # }
+/// ```
 impl core::fmt::Debug for Json<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Json::Null(()) => f.write_str("null"),
-            Json::Bool(b) => write!(f, "{b}"),
-            Json::Float(fl) => write!(f, "{fl}"),
-            Json::Int(i) => write!(f, "{i}"),
-            Json::Str(s) => write!(f, "{s:?}"),
-            Json::Array(a) => write!(f, "{a:?}"),
-
-            Json::Object(o) => {
-                f.write_str("{")?;
-                let mut idx = 0;
-                while idx < o.len() {
-                    let (k, v) = o[idx];
-                    write!(f, " {k:?}: {v:?}")?;
-                    if idx < o.len() - 1 {
-                        f.write_str(",")?;
-                    }
+        enum Frame<'j, 'a> {
+            Array(core::slice::Iter<'j, Json<'a>>),
+            Object(core::slice::Iter<'j, (&'a str, Json<'a>)>),
+        }
 
-                    idx += 1;
+        let mut stack = ser::Stack::new();
+        let mut next = Some(self);
+        loop {
+            match next.take() {
+                Some(Json::Null(())) => f.write_str("null")?,
+                Some(Json::Bool(b)) => write!(f, "{b}")?,
+                Some(Json::Float(fl)) => write!(f, "{fl}")?,
+                Some(Json::Int(i)) => write!(f, "{i}")?,
+                Some(Json::Str(s)) => write!(f, "{s:?}")?,
+                Some(Json::Array(_)) if stack.is_full() => f.write_str("[..]")?,
+                Some(Json::Object(_)) if stack.is_full() => f.write_str("{..}")?,
+                Some(Json::Array(a)) => {
+                    f.write_str("[")?;
+                    stack.push((Frame::Array(a.iter()), true));
                 }
-
-                f.write_str(" }")
+                Some(Json::Object(o)) => {
+                    f.write_str("{")?;
+                    stack.push((Frame::Object(o.iter()), true));
+                }
+                None => {}
+            }
+            let Some((frame, first)) = stack.last_mut() else {
+                return Ok(());
+            };
+            let first = core::mem::replace(first, false);
+            match frame {
+                Frame::Array(elems) => match elems.next() {
+                    Some(elem) => {
+                        if !first {
+                            f.write_str(", ")?;
+                        }
+                        next = Some(elem);
+                    }
+                    None => {
+                        stack.pop();
+                        f.write_str("]")?;
+                    }
+                },
+                Frame::Object(entries) => match entries.next() {
+                    Some((k, v)) => {
+                        if !first {
+                            f.write_str(",")?;
+                        }
+                        write!(f, " {k:?}: ")?;
+                        next = Some(v);
+                    }
+                    None => {
+                        stack.pop();
+                        f.write_str(" }")?;
+                    }
+                },
             }
         }
     }
@@ -0,0 +1,65 @@
+//! Collecting objects into maps.
+
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::Json;
+
+impl<'a> Json<'a> {
+    /// Collects the entries of an object into a map sorted by key, borrowing the keys and values.
+    /// Returns `None` if `self` is not a [`Json::Object`].
+    ///
+    /// If a key appears more than once, the map holds its last value. This differs from
+    /// [`get_val`](Self::get_val), which finds the first.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!({"b": 2, "a": 1, "b": 3});
+    ///
+    /// let map = JSON.to_btreemap().unwrap();
+    /// assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "b"]);
+    /// assert_eq!(map["b"].int(), 3);
+    /// assert_eq!(JSON["b"].int(), 2);
+    ///
+    /// assert_eq!(Json::Null(()).to_btreemap(), None);
+    /// ```
+    pub fn to_btreemap(&self) -> Option<BTreeMap<&'a str, &'a Json<'a>>> {
+        match *self {
+            Self::Object(obj) => Some(obj.iter().map(|(k, v)| (*k, v)).collect()),
+            _ => None,
+        }
+    }
+
+    /// Builds a hash map over the entries of an object, borrowing the keys and values, for
+    /// objects too large to search with [`get_val`](Self::get_val) on every lookup. Returns `None`
+    /// if `self` is not a [`Json::Object`]. Building the map takes one pass over the object, after
+    /// which each lookup takes constant time.
+    ///
+    /// If a key appears more than once, the map holds its last value, as with
+    /// [`to_btreemap`](Self::to_btreemap). This differs from [`get_val`](Self::get_val), which
+    /// finds the first.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const USERS: Json = const_json!({"ann": 3, "bob": 7, "ann": 4});
+    ///
+    /// let index = USERS.index_object().unwrap();
+    /// assert_eq!(index.len(), 2);
+    /// assert_eq!(index["bob"].int(), 7);
+    /// assert_eq!(index["ann"].int(), 4);
+    /// assert_eq!(USERS["ann"].int(), 3);
+    /// assert!(!index.contains_key("cat"));
+    ///
+    /// assert_eq!(Json::Null(()).index_object(), None);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn index_object(&self) -> Option<HashMap<&'a str, &'a Json<'a>>> {
+        match *self {
+            Self::Object(obj) => Some(obj.iter().map(|(k, v)| (*k, v)).collect()),
+            _ => None,
+        }
+    }
+}
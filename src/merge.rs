@@ -0,0 +1,157 @@
+//! Combining two documents into one.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Json, OwnedJson};
+
+/// How [`Json::merge_with`] resolves a key present in both objects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep the value from `self`.
+    PreferSelf,
+    /// Take the value from `other`.
+    PreferOther,
+    /// Merge two objects recursively, and otherwise take the value from `other`.
+    DeepMerge,
+    /// Like [`DeepMerge`](Self::DeepMerge), but append the elements of two arrays instead of
+    /// replacing one with the other.
+    ConcatArrays,
+}
+
+impl Json<'_> {
+    /// Merges `other` into `self`, producing a new owned value.
+    ///
+    /// If both are objects, the result has the entries of `self` in order, then the entries of
+    /// `other` whose keys are not in `self`. For a key present in both, [`PreferSelf`] and
+    /// [`PreferOther`] pick one of the two values, while the other strategies merge them the same
+    /// way, recursively.
+    ///
+    /// If `self` and `other` are not both objects, [`PreferSelf`] gives `self`, [`ConcatArrays`]
+    /// joins two arrays, and anything else gives `other`. So with [`DeepMerge`] and
+    /// [`ConcatArrays`], mismatched types and scalars fall back to [`PreferOther`].
+    ///
+    /// [`PreferSelf`]: MergeStrategy::PreferSelf
+    /// [`PreferOther`]: MergeStrategy::PreferOther
+    /// [`DeepMerge`]: MergeStrategy::DeepMerge
+    /// [`ConcatArrays`]: MergeStrategy::ConcatArrays
+    ///
+    /// ```rust
+    /// use const_json::{Json, MergeStrategy, const_json};
+    ///
+    /// const DEFAULTS: Json = const_json!({"port": 80, "tls": {"enabled": false, "ciphers": ["a"]}});
+    /// const USER: Json = const_json!({"tls": {"ciphers": ["b"]}, "debug": true});
+    ///
+    /// let prefer_self = DEFAULTS.merge_with(&USER, MergeStrategy::PreferSelf);
+    /// assert_eq!(prefer_self, const_json!({
+    ///     "port": 80, "tls": {"enabled": false, "ciphers": ["a"]}, "debug": true
+    /// }));
+    ///
+    /// let prefer_other = DEFAULTS.merge_with(&USER, MergeStrategy::PreferOther);
+    /// assert_eq!(prefer_other, const_json!({"port": 80, "tls": {"ciphers": ["b"]}, "debug": true}));
+    ///
+    /// let deep = DEFAULTS.merge_with(&USER, MergeStrategy::DeepMerge);
+    /// assert_eq!(deep, const_json!({
+    ///     "port": 80, "tls": {"enabled": false, "ciphers": ["b"]}, "debug": true
+    /// }));
+    ///
+    /// let concat = DEFAULTS.merge_with(&USER, MergeStrategy::ConcatArrays);
+    /// assert_eq!(concat, const_json!({
+    ///     "port": 80, "tls": {"enabled": false, "ciphers": ["a", "b"]}, "debug": true
+    /// }));
+    /// ```
+    pub fn merge_with(&self, other: &Json<'_>, strategy: MergeStrategy) -> OwnedJson {
+        let mut merged = OwnedJson::from(self);
+        merge_into(&mut merged, vec![other], strategy);
+        merged
+    }
+}
+
+/// Merges several values in order, as if by folding them with [`Json::merge_with`], so that for
+/// [`PreferOther`](MergeStrategy::PreferOther) and the deep strategies, later values override
+/// earlier ones. This suits layering configuration sources from lowest to highest priority.
+///
+/// Unlike chained calls to [`merge_with`](Json::merge_with), everything is merged into a single
+/// result in one walk, with no intermediate copies. An empty slice gives an empty object.
+///
+/// ```rust
+/// use const_json::{Json, MergeStrategy, const_json, merge_all};
+///
+/// const DEFAULTS: Json = const_json!({"port": 80, "log": {"level": "info", "color": true}});
+/// const FILE: Json = const_json!({"port": 8080});
+/// const ENV: Json = const_json!({"log": {"level": "debug"}});
+///
+/// let config = merge_all(&[&DEFAULTS, &FILE, &ENV], MergeStrategy::DeepMerge);
+/// assert_eq!(config, const_json!({"port": 8080, "log": {"level": "debug", "color": true}}));
+///
+/// assert_eq!(merge_all(&[], MergeStrategy::DeepMerge), const_json!({}));
+/// ```
+pub fn merge_all(values: &[&Json<'_>], strategy: MergeStrategy) -> OwnedJson {
+    let Some((first, rest)) = values.split_first() else {
+        return OwnedJson::Object(Vec::new());
+    };
+    let mut merged = OwnedJson::from(*first);
+    merge_into(&mut merged, rest.to_vec(), strategy);
+    merged
+}
+
+/// Merges each of `others` into `target` in place, in order, following [`Json::merge_with`].
+fn merge_into(target: &mut OwnedJson, others: Vec<&Json<'_>>, strategy: MergeStrategy) {
+    // Each value with the values left to merge into it, so very deep values cannot overflow the
+    // call stack
+    let mut pending = vec![(target, others)];
+    while let Some((target, others)) = pending.pop() {
+        // The index of the first entry with each key, and the values to merge into each entry,
+        // kept while `target` stays the same object
+        let mut index = BTreeMap::new();
+        let mut children: Vec<Vec<&Json<'_>>> = Vec::new();
+        for other in others {
+            match (&mut *target, other) {
+                (OwnedJson::Object(l), Json::Object(r)) => {
+                    if index.is_empty() {
+                        for (idx, (key, _)) in l.iter().enumerate() {
+                            index.entry(key.clone()).or_insert(idx);
+                        }
+                        children.resize_with(l.len(), Vec::new);
+                    }
+                    for (k, rv) in r.iter() {
+                        match index.get(*k) {
+                            Some(&idx) => match strategy {
+                                MergeStrategy::PreferSelf => {}
+                                MergeStrategy::PreferOther => l[idx].1 = rv.into(),
+                                _ => children[idx].push(rv),
+                            },
+                            None => {
+                                index.insert(String::from(*k), l.len());
+                                l.push(((*k).into(), rv.into()));
+                                children.push(Vec::new());
+                            }
+                        }
+                    }
+                }
+                (OwnedJson::Array(l), Json::Array(r))
+                    if strategy == MergeStrategy::ConcatArrays =>
+                {
+                    l.extend(r.iter().map(OwnedJson::from));
+                }
+                _ if strategy == MergeStrategy::PreferSelf => {}
+                (target, _) => {
+                    *target = other.into();
+                    // The values waiting for the old entries are overridden by this one
+                    index.clear();
+                    children.clear();
+                }
+            }
+        }
+        if let OwnedJson::Object(l) = target {
+            let children = l.iter_mut().zip(children);
+            pending.extend(
+                children
+                    .filter(|(_, others)| !others.is_empty())
+                    .map(|((_, value), others)| (value, others)),
+            );
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! A JSON number of any representation.
+
+use crate::Json;
+use crate::include;
+
+/// A JSON number, as returned by [`Json::number`].
+///
+/// `UInt` is for integers above `i64::MAX`. No [`Json`] variant produces it yet, but matching on
+/// all three keeps code working once one does.
+///
+/// ```rust
+/// use const_json::{Json, Number, const_json};
+///
+/// const VALUE: Json = const_json!([3, 2.5, "three"]);
+///
+/// assert_eq!(VALUE[0].number(), Some(Number::Int(3)));
+/// assert_eq!(VALUE[1].number().unwrap().as_f64(), 2.5);
+/// assert_eq!(VALUE[1].number().unwrap().as_i64(), None);
+/// assert_eq!(VALUE[2].number(), None);
+///
+/// assert_eq!(Number::Int(-1).as_u64(), None);
+/// assert_eq!(Number::UInt(u64::MAX).as_i64(), None);
+/// assert!(!Number::Float(3.0).is_integer());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Number {
+    /// A signed integer
+    Int(i64),
+    /// An unsigned integer
+    UInt(u64),
+    /// A floating point number
+    Float(f64),
+}
+
+impl Number {
+    /// Converts to an `f64`, rounding integers that have no exact representation.
+    pub const fn as_f64(self) -> f64 {
+        match self {
+            Self::Int(i) => i as f64,
+            Self::UInt(u) => u as f64,
+            Self::Float(f) => f,
+        }
+    }
+
+    /// Gets an integer that fits in an `i64`. Floats give `None`, even if they have no fractional
+    /// part.
+    pub const fn as_i64(self) -> Option<i64> {
+        match self {
+            Self::Int(i) => Some(i),
+            Self::UInt(u) if u <= i64::MAX as u64 => Some(u as i64),
+            _ => None,
+        }
+    }
+
+    /// Gets an integer that fits in a `u64`. Floats give `None`, even if they have no fractional
+    /// part.
+    pub const fn as_u64(self) -> Option<u64> {
+        match self {
+            Self::Int(i) if i >= 0 => Some(i as u64),
+            Self::UInt(u) => Some(u),
+            _ => None,
+        }
+    }
+
+    /// Whether this is one of the integer variants.
+    pub const fn is_integer(self) -> bool {
+        matches!(self, Self::Int(_) | Self::UInt(_))
+    }
+}
+
+impl Json<'_> {
+    /// Gets the number stored in an [`Int`](Json::Int) or [`Float`](Json::Float), or `None` for
+    /// any other variant.
+    pub const fn number(&self) -> Option<Number> {
+        match *self {
+            Self::Int(i) => Some(Number::Int(i)),
+            Self::Float(f) => Some(Number::Float(f)),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a [`Json::Int`], meaning the number was written without a fraction or an
+    /// exponent.
+    ///
+    /// This looks at the variant, not the value: `3.0` parses to `Float(3.0)`, which is not an
+    /// integer, even though it has no fractional part. The exception is integers too large for an
+    /// `i64`, which are stored as floats.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUES: Json = const_json!([3, 3.0, 2.5, "3"]);
+    /// const _: () = assert!(VALUES.get_idx(0).is_integer());
+    ///
+    /// assert!(!VALUES[1].is_integer() && VALUES[1].is_float());
+    /// assert!(VALUES[2].is_float());
+    /// assert!(!VALUES[3].is_integer() && !VALUES[3].is_float());
+    /// ```
+    pub const fn is_integer(&self) -> bool {
+        matches!(self, Self::Int(_))
+    }
+
+    /// Whether this is a [`Json::Float`]. See [`is_integer`](Self::is_integer) for how `3.0` is
+    /// treated.
+    pub const fn is_float(&self) -> bool {
+        matches!(self, Self::Float(_))
+    }
+
+    /// Gets a number, reading it from a string if necessary, for data that quotes its numbers as
+    /// in `"42"`.
+    ///
+    /// An [`Int`](Json::Int) or [`Float`](Json::Float) gives its value, as with
+    /// [`number`](Self::number). A [`Str`](Json::Str) must hold exactly one JSON number: an
+    /// optional `-`, then `0` or digits without a leading zero, an optional `.` followed by
+    /// digits, and an optional exponent of `e` or `E`, an optional sign and digits. Whitespace, a
+    /// leading `+`, `NaN`, hexadecimal and anything else give `None`. The string converts as the
+    /// parser would convert the same text: integers that fit in an `i64` become
+    /// [`Number::Int`], and everything else [`Number::Float`].
+    ///
+    /// ```rust
+    /// use const_json::{Json, Number, const_json};
+    ///
+    /// const VALUES: Json = const_json!(["42", "-3.14", "1e3", 7, " 42", "42px", "+1", "0x10", true]);
+    /// const ANSWER: Option<Number> = VALUES.get_idx(0).parse_number();
+    ///
+    /// assert_eq!(ANSWER, Some(Number::Int(42)));
+    /// assert_eq!(VALUES[1].parse_number(), Some(Number::Float(-3.14)));
+    /// assert_eq!(VALUES[2].parse_number(), Some(Number::Float(1000.0)));
+    /// assert_eq!(VALUES[3].parse_number(), Some(Number::Int(7)));
+    /// for idx in 4..9 {
+    ///     assert_eq!(VALUES[idx].parse_number(), None);
+    /// }
+    /// ```
+    pub const fn parse_number(&self) -> Option<Number> {
+        let Self::Str(s) = *self else {
+            return self.number();
+        };
+        let bytes = s.as_bytes();
+        if !is_number(bytes) {
+            return None;
+        }
+        match include::number(bytes, 0, bytes.len()) {
+            Self::Int(i) => Some(Number::Int(i)),
+            Self::Float(f) => Some(Number::Float(f)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Whether `bytes` is exactly one number in the JSON grammar.
+const fn is_number(bytes: &[u8]) -> bool {
+    let mut idx = 0;
+    if idx < bytes.len() && bytes[idx] == b'-' {
+        idx += 1;
+    }
+    if idx < bytes.len() && bytes[idx] == b'0' {
+        idx += 1;
+    } else if !has_digits(bytes, &mut idx) {
+        return false;
+    }
+    if idx < bytes.len() && bytes[idx] == b'.' {
+        idx += 1;
+        if !has_digits(bytes, &mut idx) {
+            return false;
+        }
+    }
+    if idx < bytes.len() && matches!(bytes[idx], b'e' | b'E') {
+        idx += 1;
+        if idx < bytes.len() && matches!(bytes[idx], b'+' | b'-') {
+            idx += 1;
+        }
+        if !has_digits(bytes, &mut idx) {
+            return false;
+        }
+    }
+    idx == bytes.len()
+}
+
+/// Skips a run of digits, returning whether there were any.
+const fn has_digits(bytes: &[u8], idx: &mut usize) -> bool {
+    let start = *idx;
+    while *idx < bytes.len() && bytes[*idx].is_ascii_digit() {
+        *idx += 1;
+    }
+    *idx > start
+}
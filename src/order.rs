@@ -0,0 +1,130 @@
+//! Ordering values totally, for sorting and for use as keys of ordered maps and sets.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::Json;
+
+/// A [`Json`] value with [`Ord`] and [`Eq`] impls that order every value, NaN included.
+///
+/// The order is that of the derived [`PartialOrd`] of [`Json`], except that floats are compared
+/// with [`f64::total_cmp`], which orders `-0.0` before `0.0` and puts NaN at the ends: after
+/// every other float if its sign bit is clear, and before every other float if it is set.
+/// Otherwise:
+/// - values of different variants are ordered by variant, in the order they are declared, so
+///   every float comes before every integer whatever their values
+/// - arrays are compared element by element, and a prefix of an array comes before it
+/// - objects are compared entry by entry in insertion order, by key and then by value
+///
+/// Two wrapped values are equal exactly when they compare as [`Ordering::Equal`].
+///
+/// ```rust
+/// use std::collections::BTreeSet;
+/// use const_json::{Json, OrderedJson, const_json};
+///
+/// const VALUES: [Json; 6] = [
+///     const_json!("b"),
+///     const_json!((f64::NAN)),
+///     const_json!(2),
+///     const_json!(0.5),
+///     const_json!(null),
+///     const_json!("b"),
+/// ];
+///
+/// let set: BTreeSet<OrderedJson> = VALUES.into_iter().map(OrderedJson).collect();
+/// let sorted: Vec<Json> = set.into_iter().map(|value| value.0).collect();
+/// assert_eq!(format!("{sorted:?}"), r#"[null, 0.5, NaN, 2, "b"]"#);
+///
+/// assert!(OrderedJson(Json::Float(-0.0)) < OrderedJson(Json::Float(0.0)));
+/// assert!(OrderedJson(const_json!([1])) < OrderedJson(const_json!([1, 0])));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct OrderedJson<'a>(pub Json<'a>);
+
+impl PartialEq for OrderedJson<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedJson<'_> {}
+
+impl PartialOrd for OrderedJson<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedJson<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        total_cmp(&self.0, &other.0)
+    }
+}
+
+fn total_cmp(l: &Json<'_>, r: &Json<'_>) -> Ordering {
+    match (*l, *r) {
+        (Json::Null(()), Json::Null(())) => Ordering::Equal,
+        (Json::Bool(l), Json::Bool(r)) => l.cmp(&r),
+        (Json::Float(l), Json::Float(r)) => l.total_cmp(&r),
+        (Json::Int(l), Json::Int(r)) => l.cmp(&r),
+        (Json::Str(l), Json::Str(r)) => l.cmp(r),
+        (Json::Array(l), Json::Array(r)) => l
+            .iter()
+            .zip(r)
+            .map(|(l, r)| total_cmp(l, r))
+            .find(|&ord| ord != Ordering::Equal)
+            .unwrap_or_else(|| l.len().cmp(&r.len())),
+        (Json::Object(l), Json::Object(r)) => l
+            .iter()
+            .zip(r)
+            .map(|((lk, lv), (rk, rv))| lk.cmp(rk).then_with(|| total_cmp(lv, rv)))
+            .find(|&ord| ord != Ordering::Equal)
+            .unwrap_or_else(|| l.len().cmp(&r.len())),
+        _ => rank(l).cmp(&rank(r)),
+    }
+}
+
+/// The position of the variant of `json` in the declaration of [`Json`].
+fn rank(json: &Json<'_>) -> u8 {
+    match json {
+        Json::Null(()) => 0,
+        Json::Bool(_) => 1,
+        Json::Float(_) => 2,
+        Json::Int(_) => 3,
+        Json::Str(_) => 4,
+        Json::Array(_) => 5,
+        Json::Object(_) => 6,
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Json<'a> {
+    /// Gets the entries of an object sorted by value, in the order of [`OrderedJson`], for reading
+    /// the top or bottom few of a `{name: score}` object. The sort is stable, so entries with
+    /// equal values keep their insertion order. Returns an empty vector if `self` is not a
+    /// [`Json::Object`].
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const SCORES: Json = const_json!({"ann": 7, "bob": 3, "cy": 9, "dee": 3});
+    ///
+    /// let ranked = SCORES.entries_by_value();
+    /// let names: Vec<&str> = ranked.iter().map(|(name, _)| *name).collect();
+    /// assert_eq!(names, ["bob", "dee", "ann", "cy"]);
+    ///
+    /// let (best, score) = ranked.last().unwrap();
+    /// assert_eq!((*best, score.int()), ("cy", 9));
+    ///
+    /// assert!(const_json!([3, 1]).entries_by_value().is_empty());
+    /// ```
+    pub fn entries_by_value(&self) -> Vec<(&'a str, &'a Json<'a>)> {
+        let Self::Object(obj) = *self else {
+            return Vec::new();
+        };
+        let mut entries: Vec<_> = obj.iter().map(|(key, value)| (*key, value)).collect();
+        entries.sort_by(|(_, l), (_, r)| total_cmp(l, r));
+        entries
+    }
+}
@@ -0,0 +1,668 @@
+//! A heap-allocated JSON value.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Index;
+use core::slice;
+
+use crate::{Json, JsonError};
+
+/// An owned JSON value, as produced by the runtime parser.
+///
+/// This mirrors [`Json`] variant for variant, but owns its strings and children, so it can be built
+/// from data that is only available at runtime.
+#[derive(Clone, PartialEq, PartialOrd)]
+pub enum OwnedJson {
+    /// `null`
+    Null(()),
+    /// `true` or `false`
+    Bool(bool),
+    /// A floating point number
+    Float(f64),
+    /// A 64-bit signed integer
+    Int(i64),
+    /// A string
+    Str(String),
+    /// An untyped Json array
+    Array(Vec<OwnedJson>),
+    /// A Json key-value map
+    Object(Vec<(String, OwnedJson)>),
+}
+
+impl OwnedJson {
+    /// Gets a value stored at the given key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    /// - `self` is not an [`OwnedJson::Object`]
+    /// - the key could not be found
+    pub fn get_val(&self, key: &str) -> &Self {
+        match self {
+            Self::Object(obj) => match obj.iter().find(|(k, _)| k == key) {
+                Some((_, v)) => v,
+                None => panic!("key not found"),
+            },
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    /// Gets a value stored at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    /// - `self` is not an [`OwnedJson::Array`]
+    /// - the index was out of range
+    pub fn get_idx(&self, index: usize) -> &Self {
+        match self {
+            Self::Array(arr) => &arr[index],
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    /// Adds an element at the end of an array.
+    ///
+    /// Fails with [`JsonError::WrongType`], leaving `self` as it was, if `self` is not an
+    /// [`OwnedJson::Array`].
+    ///
+    /// ```rust
+    /// use const_json::{JsonError, OwnedJson, const_json};
+    ///
+    /// let mut results = OwnedJson::Array(Vec::new());
+    /// for i in 0..3 {
+    ///     results.push(OwnedJson::Int(i)).unwrap();
+    /// }
+    /// assert_eq!(results, const_json!([0, 1, 2]));
+    ///
+    /// let mut object = OwnedJson::from(const_json!({}));
+    /// assert_eq!(
+    ///     object.push(OwnedJson::Null(())),
+    ///     Err(JsonError::WrongType { expected: "array", found: "object" }),
+    /// );
+    /// ```
+    pub fn push(&mut self, value: Self) -> Result<(), JsonError<'static>> {
+        match self {
+            Self::Array(arr) => {
+                arr.push(value);
+                Ok(())
+            }
+            _ => Err(JsonError::WrongType {
+                expected: "array",
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// Adds an element at the end of an array, returning the array, for chaining.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let list = OwnedJson::Array(Vec::new())
+    ///     .with_pushed(OwnedJson::Int(1))
+    ///     .with_pushed(OwnedJson::Str("two".into()));
+    /// assert_eq!(list, const_json!([1, "two"]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not an [`OwnedJson::Array`]
+    pub fn with_pushed(mut self, value: Self) -> Self {
+        match self.push(value) {
+            Ok(()) => self,
+            Err(_) => panic!("wrong variant"),
+        }
+    }
+
+    /// Adds every element of `iter` at the end of an array, in order.
+    ///
+    /// Fails with [`JsonError::WrongType`], leaving `self` as it was, if `self` is not an
+    /// [`OwnedJson::Array`].
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut list = OwnedJson::from(const_json!([1]));
+    /// list.extend_array((2..4).map(OwnedJson::Int)).unwrap();
+    /// assert_eq!(list, const_json!([1, 2, 3]));
+    /// ```
+    pub fn extend_array(
+        &mut self,
+        iter: impl IntoIterator<Item = Self>,
+    ) -> Result<(), JsonError<'static>> {
+        match self {
+            Self::Array(arr) => {
+                arr.extend(iter);
+                Ok(())
+            }
+            _ => Err(JsonError::WrongType {
+                expected: "array",
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// Adds every entry of `iter` to an object, in order.
+    ///
+    /// The last value for a key wins: an entry whose key is already in the object replaces the
+    /// value of the first entry with that key, keeping its position, and other entries are added
+    /// at the end.
+    ///
+    /// Fails with [`JsonError::WrongType`], leaving `self` as it was, if `self` is not an
+    /// [`OwnedJson::Object`].
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut config = OwnedJson::from(const_json!({"host": "localhost", "port": 80}));
+    /// let overrides = [("port", 8080), ("debug", 1), ("port", 9090)];
+    /// config
+    ///     .extend_object(overrides.map(|(k, v)| (k.into(), OwnedJson::Int(v))))
+    ///     .unwrap();
+    /// assert_eq!(config, const_json!({"host": "localhost", "port": 9090, "debug": 1}));
+    /// ```
+    pub fn extend_object(
+        &mut self,
+        iter: impl IntoIterator<Item = (String, Self)>,
+    ) -> Result<(), JsonError<'static>> {
+        match self {
+            Self::Object(obj) => {
+                // The index of the first entry with each key
+                let mut index = BTreeMap::new();
+                for (idx, (key, _)) in obj.iter().enumerate() {
+                    index.entry(key.clone()).or_insert(idx);
+                }
+                for (key, value) in iter {
+                    match index.get(&key) {
+                        Some(&idx) => obj[idx].1 = value,
+                        None => {
+                            index.insert(key.clone(), obj.len());
+                            obj.push((key, value));
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(JsonError::WrongType {
+                expected: "object",
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// Consumes an object, giving its entries in order, or `None` if `self` is not an
+    /// [`OwnedJson::Object`].
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let json = OwnedJson::from(const_json!({"b": 1, "a": [2]}));
+    /// let entries = json.into_entries().unwrap();
+    /// assert_eq!(entries[0].0, "b");
+    /// assert_eq!(entries[1].1, const_json!([2]));
+    /// ```
+    pub fn into_entries(self) -> Option<Vec<(String, Self)>> {
+        match self {
+            Self::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Consumes an array, giving its elements in order, or `None` if `self` is not an
+    /// [`OwnedJson::Array`].
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let json = OwnedJson::from(const_json!([1, "two"]));
+    /// assert_eq!(json.into_elements().unwrap(), [const_json!(1), const_json!("two")]);
+    /// assert_eq!(OwnedJson::Null(()).into_elements(), None);
+    /// ```
+    pub fn into_elements(self) -> Option<Vec<Self>> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// Removes the entry with the given key from an object and returns its value, keeping the
+    /// remaining entries in order. If the key appears more than once, only the first entry is
+    /// removed.
+    ///
+    /// Returns `None` if `self` is not an [`OwnedJson::Object`] or the key could not be found.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!({"a": 1, "b": 2, "c": 3, "d": 4}));
+    ///
+    /// assert_eq!(json.remove("a").unwrap(), const_json!(1));
+    /// assert_eq!(json.remove("c").unwrap(), const_json!(3));
+    /// assert_eq!(json.remove("d").unwrap(), const_json!(4));
+    /// assert_eq!(json, const_json!({"b": 2}));
+    ///
+    /// assert_eq!(json.remove("a"), None);
+    /// assert_eq!(OwnedJson::Null(()).remove("a"), None);
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<Self> {
+        match self {
+            Self::Object(obj) => {
+                let idx = obj.iter().position(|(k, _)| k == key)?;
+                Some(obj.remove(idx).1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the element at the given index from an array and returns it, shifting the
+    /// elements after it down.
+    ///
+    /// Returns `None` if `self` is not an [`OwnedJson::Array`] or the index is out of range.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!(["a", "b", "c"]));
+    ///
+    /// assert_eq!(json.remove_index(1).unwrap(), const_json!("b"));
+    /// assert_eq!(json.remove_index(2), None);
+    /// assert_eq!(json, const_json!(["a", "c"]));
+    /// ```
+    pub fn remove_index(&mut self, index: usize) -> Option<Self> {
+        match self {
+            Self::Array(arr) if index < arr.len() => Some(arr.remove(index)),
+            _ => None,
+        }
+    }
+
+    /// Removes the entry with the given key from an object and returns its value, moving the last
+    /// entry into its place, like [`Vec::swap_remove`]. This takes constant time after finding the
+    /// key, but unlike [`remove`](Self::remove) does not keep the entries in order. If the key
+    /// appears more than once, only the first entry is removed.
+    ///
+    /// Returns `None` if `self` is not an [`OwnedJson::Object`] or the key could not be found.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!({"a": 1, "b": 2, "c": 3, "d": 4}));
+    ///
+    /// assert_eq!(json.swap_remove("a").unwrap(), const_json!(1));
+    /// assert_eq!(json, const_json!({"d": 4, "b": 2, "c": 3}));
+    /// assert_eq!(json.swap_remove("c").unwrap(), const_json!(3));
+    /// assert_eq!(json, const_json!({"d": 4, "b": 2}));
+    ///
+    /// assert_eq!(json.swap_remove("a"), None);
+    /// assert_eq!(OwnedJson::Null(()).swap_remove("a"), None);
+    /// ```
+    pub fn swap_remove(&mut self, key: &str) -> Option<Self> {
+        match self {
+            Self::Object(obj) => {
+                let idx = obj.iter().position(|(k, _)| k == key)?;
+                Some(obj.swap_remove(idx).1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the element at the given index from an array and returns it, moving the last
+    /// element into its place, like [`Vec::swap_remove`]. This takes constant time, but unlike
+    /// [`remove_index`](Self::remove_index) does not keep the elements in order.
+    ///
+    /// Returns `None` if `self` is not an [`OwnedJson::Array`] or the index is out of range.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!(["a", "b", "c", "d"]));
+    ///
+    /// assert_eq!(json.swap_remove_index(1).unwrap(), const_json!("b"));
+    /// assert_eq!(json, const_json!(["a", "d", "c"]));
+    /// assert_eq!(json.swap_remove_index(2).unwrap(), const_json!("c"));
+    /// assert_eq!(json.swap_remove_index(2), None);
+    /// assert_eq!(json, const_json!(["a", "d"]));
+    /// ```
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<Self> {
+        match self {
+            Self::Array(arr) if index < arr.len() => Some(arr.swap_remove(index)),
+            _ => None,
+        }
+    }
+
+    /// Keeps only the entries of an object for which `f`, given the key and value, returns
+    /// `true`, in their original order. Does nothing if `self` is not an [`OwnedJson::Object`].
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut config = OwnedJson::from(const_json!({
+    ///     "host": "db", "password": "hunter2", "port": 5432, "api_key": "k", "user": "app"
+    /// }));
+    /// config.retain(|key, _| !matches!(key, "password" | "api_key"));
+    /// assert_eq!(config, const_json!({"host": "db", "port": 5432, "user": "app"}));
+    ///
+    /// let mut idx = 0;
+    /// config.retain(|_, _| {
+    ///     idx += 1;
+    ///     idx % 2 == 1
+    /// });
+    /// assert_eq!(config, const_json!({"host": "db", "user": "app"}));
+    ///
+    /// let mut list = OwnedJson::from(const_json!([1, 2]));
+    /// list.retain(|_, _| false);
+    /// assert_eq!(list, const_json!([1, 2]));
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&str, &OwnedJson) -> bool) {
+        if let Self::Object(obj) = self {
+            obj.retain(|(key, value)| f(key, value));
+        }
+    }
+
+    /// Keeps only the elements of an array for which `f` returns `true`, in their original order.
+    /// Does nothing if `self` is not an [`OwnedJson::Array`].
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut plugins = OwnedJson::from(const_json!([
+    ///     {"name": "a", "enabled": true},
+    ///     {"name": "b", "enabled": false},
+    ///     {"name": "c", "enabled": true}
+    /// ]));
+    /// plugins.retain_elements(|plugin| plugin["enabled"] == const_json!(true));
+    /// assert_eq!(plugins, const_json!([
+    ///     {"name": "a", "enabled": true},
+    ///     {"name": "c", "enabled": true}
+    /// ]));
+    /// ```
+    pub fn retain_elements(&mut self, f: impl FnMut(&OwnedJson) -> bool) {
+        if let Self::Array(arr) = self {
+            arr.retain(f);
+        }
+    }
+
+    /// Rewrites every object key through `f`, in `self` and in every array and object inside it,
+    /// keeping entries in order. This is for moving between naming conventions, such as
+    /// `snake_case` and `camelCase`.
+    ///
+    /// If several keys of one object map to the same key, the last of those entries wins, and the
+    /// others are removed.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// fn camel_case(key: &str) -> String {
+    ///     let mut parts = key.split('_');
+    ///     let mut out = parts.next().unwrap_or_default().to_string();
+    ///     for part in parts {
+    ///         let mut chars = part.chars();
+    ///         out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+    ///         out.push_str(chars.as_str());
+    ///     }
+    ///     out
+    /// }
+    ///
+    /// let mut json = OwnedJson::from(const_json!({
+    ///     "user_id": 7,
+    ///     "home_address": {"street_name": "Main", "zip_code": "1"},
+    ///     "recent_orders": [{"order_id": 1}]
+    /// }));
+    /// json.map_keys(camel_case);
+    /// assert_eq!(json, const_json!({
+    ///     "userId": 7,
+    ///     "homeAddress": {"streetName": "Main", "zipCode": "1"},
+    ///     "recentOrders": [{"orderId": 1}]
+    /// }));
+    ///
+    /// let mut json = OwnedJson::from(const_json!({"user_id": 1, "userId": 2, "name": "x"}));
+    /// json.map_keys(camel_case);
+    /// assert_eq!(json, const_json!({"userId": 2, "name": "x"}));
+    /// ```
+    pub fn map_keys(&mut self, f: impl Fn(&str) -> String) {
+        // The values left to visit, so very deep documents cannot overflow the call stack
+        let mut pending = Vec::from([self]);
+        while let Some(value) = pending.pop() {
+            match value {
+                Self::Array(arr) => pending.extend(arr.iter_mut()),
+                Self::Object(obj) => {
+                    for (key, _) in obj.iter_mut() {
+                        *key = f(key);
+                    }
+                    // The last entry with each key wins, so keep only entries whose key is not
+                    // used again later
+                    let mut later = BTreeSet::new();
+                    let mut keep: Vec<bool> =
+                        obj.iter().rev().map(|(k, _)| later.insert(k)).collect();
+                    obj.retain(|_| keep.pop().unwrap_or(false));
+                    pending.extend(obj.iter_mut().map(|(_, value)| value));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Renames the first entry of an object with the key `from` to `to`, keeping its position and
+    /// value, and returns whether there was one to rename. Returns `false`, changing nothing, if
+    /// `self` is not an [`OwnedJson::Object`] or has no such key.
+    ///
+    /// If the object already has an entry with the key `to`, it is overwritten: the renamed entry
+    /// stays where `from` was, and the old entry for `to` is removed.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!({"id": 1, "colour": "red", "size": 2}));
+    /// assert!(json.rename_key("colour", "color"));
+    /// assert_eq!(json, const_json!({"id": 1, "color": "red", "size": 2}));
+    /// assert!(!json.rename_key("colour", "color"));
+    ///
+    /// // Renaming into an existing key replaces that entry
+    /// assert!(json.rename_key("size", "id"));
+    /// assert_eq!(json, const_json!({"color": "red", "id": 2}));
+    /// ```
+    pub fn rename_key(&mut self, from: &str, to: &str) -> bool {
+        let Self::Object(obj) = self else {
+            return false;
+        };
+        let Some(idx) = obj.iter().position(|(k, _)| k == from) else {
+            return false;
+        };
+        if from != to {
+            obj[idx].0 = to.into();
+            let mut pos = 0;
+            obj.retain(|(k, _)| {
+                pos += 1;
+                pos - 1 == idx || k != to
+            });
+        }
+        true
+    }
+}
+
+impl From<&Json<'_>> for OwnedJson {
+    fn from(value: &Json<'_>) -> Self {
+        // The containers being copied, innermost last, so very deep values cannot overflow the
+        // call stack
+        let mut stack = Vec::new();
+        let mut next = value;
+        loop {
+            let mut copied = match *next {
+                Json::Null(()) => Some(Self::Null(())),
+                Json::Bool(b) => Some(Self::Bool(b)),
+                Json::Float(f) => Some(Self::Float(f)),
+                Json::Int(i) => Some(Self::Int(i)),
+                Json::Str(s) => Some(Self::Str(s.into())),
+                Json::Array(a) => {
+                    stack.push(CopyFrame::Array(a.iter(), Vec::with_capacity(a.len())));
+                    None
+                }
+                Json::Object(o) => {
+                    stack.push(CopyFrame::Object(o.iter(), Vec::with_capacity(o.len())));
+                    None
+                }
+            };
+            // Hand each finished copy to its container, until one has more elements to copy
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    // Every container pushes a frame, so this is the finished copy of `value`
+                    return copied.unwrap_or(Self::Null(()));
+                };
+                let more = match frame {
+                    CopyFrame::Array(iter, items) => {
+                        items.extend(copied.take());
+                        iter.next()
+                    }
+                    CopyFrame::Object(iter, entries) => {
+                        if let Some(copied) = copied.take()
+                            && let Some((_, value)) = entries.last_mut()
+                        {
+                            *value = copied;
+                        }
+                        iter.next().map(|(key, value)| {
+                            entries.push(((*key).into(), Self::Null(())));
+                            value
+                        })
+                    }
+                };
+                match more {
+                    Some(value) => {
+                        next = value;
+                        break;
+                    }
+                    None => {
+                        copied = stack.pop().map(|frame| match frame {
+                            CopyFrame::Array(_, items) => Self::Array(items),
+                            CopyFrame::Object(_, entries) => Self::Object(entries),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A container that [`OwnedJson::from`] is copying, with the elements left to copy and the copies
+/// made so far.
+enum CopyFrame<'j, 'a> {
+    Array(slice::Iter<'j, Json<'a>>, Vec<OwnedJson>),
+    /// The last entry holds a placeholder until its value has been copied.
+    Object(
+        slice::Iter<'j, (&'a str, Json<'a>)>,
+        Vec<(String, OwnedJson)>,
+    ),
+}
+
+impl From<Json<'_>> for OwnedJson {
+    fn from(value: Json<'_>) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl PartialEq<Json<'_>> for OwnedJson {
+    fn eq(&self, other: &Json<'_>) -> bool {
+        match (self, other) {
+            (Self::Null(()), Json::Null(())) => true,
+            (Self::Bool(l), Json::Bool(r)) => l == r,
+            (Self::Float(l), Json::Float(r)) => l == r,
+            (Self::Int(l), Json::Int(r)) => l == r,
+            (Self::Str(l), Json::Str(r)) => l == r,
+            (Self::Array(l), Json::Array(r)) => {
+                l.len() == r.len() && l.iter().zip(*r).all(|(l, r)| l == r)
+            }
+            (Self::Object(l), Json::Object(r)) => {
+                l.len() == r.len()
+                    && l.iter()
+                        .zip(*r)
+                        .all(|((lk, lv), (rk, rv))| lk == rk && lv == rv)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<OwnedJson> for Json<'_> {
+    fn eq(&self, other: &OwnedJson) -> bool {
+        other == self
+    }
+}
+
+impl Index<usize> for OwnedJson {
+    type Output = OwnedJson;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get_idx(index)
+    }
+}
+
+impl Index<&str> for OwnedJson {
+    type Output = OwnedJson;
+
+    fn index(&self, index: &str) -> &Self::Output {
+        self.get_val(index)
+    }
+}
+
+/// Writes the same as the [`Debug`](core::fmt::Debug) impl of [`Json`], also with an explicit stack
+/// rather than recursion.
+impl core::fmt::Debug for OwnedJson {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        enum Frame<'j> {
+            Array(core::slice::Iter<'j, OwnedJson>),
+            Object(core::slice::Iter<'j, (String, OwnedJson)>),
+        }
+
+        let mut stack = Vec::new();
+        let mut next = Some(self);
+        loop {
+            match next.take() {
+                Some(OwnedJson::Null(())) => f.write_str("null")?,
+                Some(OwnedJson::Bool(b)) => write!(f, "{b}")?,
+                Some(OwnedJson::Float(fl)) => write!(f, "{fl}")?,
+                Some(OwnedJson::Int(i)) => write!(f, "{i}")?,
+                Some(OwnedJson::Str(s)) => write!(f, "{s:?}")?,
+                Some(OwnedJson::Array(a)) => {
+                    f.write_str("[")?;
+                    stack.push((Frame::Array(a.iter()), true));
+                }
+                Some(OwnedJson::Object(o)) => {
+                    f.write_str("{")?;
+                    stack.push((Frame::Object(o.iter()), true));
+                }
+                None => {}
+            }
+            let Some((frame, first)) = stack.last_mut() else {
+                return Ok(());
+            };
+            let first = core::mem::replace(first, false);
+            match frame {
+                Frame::Array(elems) => match elems.next() {
+                    Some(elem) => {
+                        if !first {
+                            f.write_str(", ")?;
+                        }
+                        next = Some(elem);
+                    }
+                    None => {
+                        stack.pop();
+                        f.write_str("]")?;
+                    }
+                },
+                Frame::Object(entries) => match entries.next() {
+                    Some((k, v)) => {
+                        if !first {
+                            f.write_str(",")?;
+                        }
+                        write!(f, " {k:?}: ")?;
+                        next = Some(v);
+                    }
+                    None => {
+                        stack.pop();
+                        f.write_str(" }")?;
+                    }
+                },
+            }
+        }
+    }
+}
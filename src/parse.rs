@@ -0,0 +1,1407 @@
+//! Runtime handling of JSON text.
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::escape::{decode_escape, unescape};
+use crate::{CowJson, OwnedJson};
+
+/// An error encountered while reading JSON text.
+///
+/// ```rust
+/// let err = const_json::parse("{\n  \"a\": [1,\r\n    2}").unwrap_err();
+/// assert_eq!((err.line, err.column), (3, 6));
+/// assert_eq!(err.to_string(), "error at line 3, column 6: unexpected '}'");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// What went wrong.
+    pub kind: ParseErrorKind,
+    /// The byte offset into the input at which the error was detected.
+    pub offset: usize,
+    /// The 1-based line of [`offset`](Self::offset). Lines are ended by `\n` or `\r\n`.
+    pub line: usize,
+    /// The 1-based column of [`offset`](Self::offset), counted in characters.
+    pub column: usize,
+}
+
+impl ParseError {
+    /// Creates an error at `offset` into `src`, working out its line and column.
+    pub(crate) fn new(kind: ParseErrorKind, offset: usize, src: &[u8]) -> Self {
+        let before = &src[..offset.min(src.len())];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        // Count characters rather than bytes, by skipping UTF-8 continuation bytes
+        let column = before[line_start..]
+            .iter()
+            .filter(|&&b| b & 0xc0 != 0x80)
+            .count();
+        Self {
+            kind,
+            offset,
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: column + 1,
+        }
+    }
+}
+
+/// The reason a [`ParseError`] was raised.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The input ended in the middle of a value.
+    UnexpectedEof,
+    /// A character that cannot appear at this position.
+    UnexpectedChar(char),
+    /// A backslash inside a string that does not start a valid escape sequence.
+    InvalidEscape,
+    /// A `\u` escape that is not followed by four hexadecimal digits.
+    InvalidUnicodeEscape,
+    /// A `\u` escape of a UTF-16 surrogate that is not part of a high-low pair.
+    LoneSurrogate,
+    /// A control character (below U+0020) that appears unescaped inside a string.
+    ControlCharacter,
+    /// A number that does not follow the JSON number grammar.
+    InvalidNumber,
+    /// Bytes that are not valid UTF-8.
+    InvalidUtf8,
+    /// Something other than whitespace follows the top-level value.
+    TrailingCharacters,
+    /// Arrays and objects are nested deeper than [`ParserOptions::max_depth`].
+    DepthLimitExceeded,
+    /// `true`, `false` or `null` written with capitals, such as `True` or `NULL`, which needs
+    /// [`ParserOptions::case_insensitive_literals`].
+    MiscasedLiteral,
+    /// A key, given unescaped, that appears more than once in the same object, under
+    /// [`DuplicateKeyPolicy::Error`]. The error's offset is that of the repeated key.
+    DuplicateKey(String),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => f.write_str("unexpected end of input"),
+            Self::UnexpectedChar(c) => write!(f, "unexpected {c:?}"),
+            Self::InvalidEscape => f.write_str("invalid escape sequence"),
+            Self::InvalidUnicodeEscape => f.write_str("invalid unicode escape"),
+            Self::LoneSurrogate => f.write_str("lone surrogate in unicode escape"),
+            Self::ControlCharacter => f.write_str("unescaped control character in string"),
+            Self::InvalidNumber => f.write_str("invalid number"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Self::TrailingCharacters => f.write_str("trailing characters"),
+            Self::DepthLimitExceeded => f.write_str("nesting depth limit exceeded"),
+            Self::MiscasedLiteral => f.write_str("true, false and null must be lowercase"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key '{key}' in object"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error at line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+/// The extensions to strict JSON that a [`Reader`] accepts.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Syntax {
+    /// `//` and `/* */` comments count as whitespace.
+    pub(crate) comments: bool,
+    /// A `,` may follow the last element of an array or object.
+    pub(crate) trailing_commas: bool,
+    /// Strings may be delimited by `'` as well as `"`.
+    pub(crate) single_quotes: bool,
+    /// Object keys may be bare identifiers.
+    pub(crate) unquoted_keys: bool,
+    /// Numbers may be hexadecimal, start with `+` or `.`, end with `.`, or be `Infinity` or `NaN`.
+    pub(crate) json5_numbers: bool,
+    /// `true`, `false` and `null` may be written in any case.
+    pub(crate) case_insensitive_literals: bool,
+}
+
+/// A cursor over JSON text that recognises and validates individual tokens.
+pub(crate) struct Reader<'s> {
+    pub(crate) src: &'s [u8],
+    pub(crate) pos: usize,
+    pub(crate) syntax: Syntax,
+}
+
+impl<'s> Reader<'s> {
+    pub(crate) const fn new(src: &'s [u8], syntax: Syntax) -> Self {
+        Self {
+            src,
+            pos: 0,
+            syntax,
+        }
+    }
+
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(kind, self.pos, self.src)
+    }
+
+    /// Reports whatever is at the cursor as unexpected.
+    pub(crate) fn unexpected(&self) -> ParseError {
+        match self.src.get(self.pos..) {
+            None | Some([]) => self.error(ParseErrorKind::UnexpectedEof),
+            Some(rest) => match (1..=rest.len().min(4))
+                .find_map(|n| core::str::from_utf8(&rest[..n]).ok())
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) => self.error(ParseErrorKind::UnexpectedChar(c)),
+                None => self.error(ParseErrorKind::InvalidUtf8),
+            },
+        }
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'/') if self.syntax.comments => match self.src.get(self.pos + 1) {
+                    Some(b'/') => {
+                        while !matches!(self.peek(), None | Some(b'\n')) {
+                            self.pos += 1;
+                        }
+                    }
+                    Some(b'*') => {
+                        let start = self.pos;
+                        self.pos += 2;
+                        loop {
+                            match self.peek() {
+                                None => {
+                                    self.pos = start;
+                                    return Err(self.error(ParseErrorKind::UnexpectedEof));
+                                }
+                                Some(b'*') if self.src.get(self.pos + 1) == Some(&b'/') => {
+                                    self.pos += 2;
+                                    break;
+                                }
+                                Some(_) => self.pos += 1,
+                            }
+                        }
+                    }
+                    _ => return Ok(()),
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Consumes `byte`, or fails without moving the cursor.
+    pub(crate) fn expect(&mut self, byte: u8) -> Result<(), ParseError> {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Consumes one of `true`, `false` or `null`, given in lowercase. The same word in other
+    /// cases is only accepted with `case_insensitive_literals`.
+    pub(crate) fn literal(&mut self, word: &[u8]) -> Result<(), ParseError> {
+        let end = self.pos + word.len();
+        match self.src.get(self.pos..end) {
+            Some(text) if text == word => {}
+            Some(text) if text.eq_ignore_ascii_case(word) => {
+                if !self.syntax.case_insensitive_literals {
+                    return Err(self.error(ParseErrorKind::MiscasedLiteral));
+                }
+            }
+            // Find the first byte that is wrong
+            _ => {
+                for &b in word {
+                    self.expect(b)?;
+                }
+                return Ok(());
+            }
+        }
+        self.pos = end;
+        Ok(())
+    }
+
+    /// Consumes a string token, including its quotes.
+    ///
+    /// Escape sequences must be valid, and control characters must be escaped, as RFC 8259
+    /// requires.
+    pub(crate) fn string(&mut self) -> Result<(), ParseError> {
+        let quote = match self.peek() {
+            Some(b'\'') if self.syntax.single_quotes => b'\'',
+            _ => b'"',
+        };
+        self.expect(quote)?;
+        loop {
+            match self.peek() {
+                None => return Err(self.error(ParseErrorKind::UnexpectedEof)),
+                Some(b) if b == quote => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b'\\') => match decode_escape(self.src, self.pos, self.syntax.single_quotes) {
+                    Ok((_, len)) => self.pos += len,
+                    Err(kind) => return Err(self.error(kind)),
+                },
+                Some(0x00..=0x1f) => return Err(self.error(ParseErrorKind::ControlCharacter)),
+                Some(_) => self.pos += 1,
+            }
+        }
+    }
+
+    /// Consumes a number token following the JSON number grammar.
+    pub(crate) fn number(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        let invalid = |r: &mut Self| {
+            r.pos = start;
+            Err(r.error(ParseErrorKind::InvalidNumber))
+        };
+        let json5 = self.syntax.json5_numbers;
+
+        match self.peek() {
+            Some(b'-') => self.pos += 1,
+            Some(b'+') if json5 => self.pos += 1,
+            _ => {}
+        }
+        if json5 {
+            let rest = &self.src[self.pos..];
+            if rest.starts_with(b"Infinity") {
+                self.pos += 8;
+                return Ok(());
+            } else if rest.starts_with(b"NaN") {
+                self.pos += 3;
+                return Ok(());
+            } else if rest.starts_with(b"0x") || rest.starts_with(b"0X") {
+                self.pos += 2;
+                if !self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+                    return invalid(self);
+                }
+                while self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+                    self.pos += 1;
+                }
+                return Ok(());
+            }
+        }
+        let integral = match self.peek() {
+            // A leading zero must be the whole integer part
+            Some(b'0') if self.src.get(self.pos + 1).is_some_and(u8::is_ascii_digit) => {
+                return invalid(self);
+            }
+            Some(b'0') => {
+                self.pos += 1;
+                true
+            }
+            Some(b'1'..=b'9') => {
+                self.digits();
+                true
+            }
+            Some(b'.') if json5 => false,
+            _ => return invalid(self),
+        };
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            let fraction = self.peek().is_some_and(|b| b.is_ascii_digit());
+            // JSON5 allows either side of the point to be empty, but not both
+            if !(fraction || json5 && integral) {
+                return invalid(self);
+            }
+            self.digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                return invalid(self);
+            }
+            self.digits();
+        }
+        Ok(())
+    }
+
+    /// Consumes an ECMAScript-style identifier made of ASCII letters, digits, `_` and `$`.
+    pub(crate) fn identifier(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$') => self.pos += 1,
+            _ => return Err(self.unexpected()),
+        }
+        while let Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'$') = self.peek() {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+    }
+
+    /// Fails unless only whitespace remains.
+    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace()?;
+        if self.pos < self.src.len() {
+            Err(self.error(ParseErrorKind::TrailingCharacters))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Strips insignificant whitespace from JSON text, without building a value tree.
+///
+/// Strings and numbers are copied through exactly as written. The input is checked to be a
+/// single well-formed JSON value, so malformed text is reported rather than mangled.
+///
+/// ```rust
+/// use const_json::minify;
+///
+/// let src = r#"{
+///     "name": "a  b",
+///     "list": [ 1.50, -2e3, true, null ]
+/// }"#;
+/// assert_eq!(minify(src).unwrap(), r#"{"name":"a  b","list":[1.50,-2e3,true,null]}"#);
+///
+/// assert!(minify("[1, 2").is_err());
+/// assert!(minify(r#"{"a" 1}"#).is_err());
+///
+/// // Nesting is limited as in `parse`
+/// let err = minify(&"[".repeat(1_000_000)).unwrap_err();
+/// assert_eq!(err.kind, const_json::ParseErrorKind::DepthLimitExceeded);
+/// ```
+pub fn minify(src: &str) -> Result<String, ParseError> {
+    let mut reader = Reader::new(src.as_bytes(), Syntax::default());
+    let mut out = String::with_capacity(src.len());
+    minify_value(&mut reader, src, &mut out, ParserOptions::MAX_DEPTH)?;
+    reader.finish()?;
+    Ok(out)
+}
+
+/// Copies one value from `r` to `out`, inside which arrays and objects may be nested `depth` deep.
+fn minify_value(
+    r: &mut Reader<'_>,
+    src: &str,
+    out: &mut String,
+    depth: usize,
+) -> Result<(), ParseError> {
+    r.skip_whitespace()?;
+    let start = r.pos;
+    if matches!(r.peek(), Some(b'{' | b'[')) && depth == 0 {
+        return Err(r.error(ParseErrorKind::DepthLimitExceeded));
+    }
+    match r.peek() {
+        Some(b'{') => {
+            r.pos += 1;
+            out.push('{');
+            r.skip_whitespace()?;
+            if r.peek() == Some(b'}') {
+                r.pos += 1;
+                out.push('}');
+                return Ok(());
+            }
+            loop {
+                r.skip_whitespace()?;
+                let key = r.pos;
+                r.string()?;
+                out.push_str(&src[key..r.pos]);
+                r.skip_whitespace()?;
+                r.expect(b':')?;
+                out.push(':');
+                minify_value(r, src, out, depth - 1)?;
+                r.skip_whitespace()?;
+                match r.peek() {
+                    Some(b',') => out.push(','),
+                    Some(b'}') => {
+                        r.pos += 1;
+                        out.push('}');
+                        return Ok(());
+                    }
+                    _ => return Err(r.unexpected()),
+                }
+                r.pos += 1;
+            }
+        }
+        Some(b'[') => {
+            r.pos += 1;
+            out.push('[');
+            r.skip_whitespace()?;
+            if r.peek() == Some(b']') {
+                r.pos += 1;
+                out.push(']');
+                return Ok(());
+            }
+            loop {
+                minify_value(r, src, out, depth - 1)?;
+                r.skip_whitespace()?;
+                match r.peek() {
+                    Some(b',') => out.push(','),
+                    Some(b']') => {
+                        r.pos += 1;
+                        out.push(']');
+                        return Ok(());
+                    }
+                    _ => return Err(r.unexpected()),
+                }
+                r.pos += 1;
+            }
+        }
+        Some(b'"') => r.string()?,
+        Some(b'-' | b'0'..=b'9') => r.number()?,
+        Some(b't' | b'T') => r.literal(b"true")?,
+        Some(b'f' | b'F') => r.literal(b"false")?,
+        Some(b'n' | b'N') => r.literal(b"null")?,
+        _ => return Err(r.unexpected()),
+    }
+    out.push_str(&src[start..r.pos]);
+    Ok(())
+}
+
+/// Options for the runtime parser, used by [`parse_with`].
+///
+/// Every option can be set either directly or through the builder method of the same name. The
+/// [`Default`], also given by [`strict`](Self::strict), accepts exactly RFC 8259 JSON and is what
+/// [`parse`] uses. [`json5`](Self::json5) and [`lenient`](Self::lenient) turn the extensions on
+/// together.
+///
+/// ```rust
+/// use const_json::{ParserOptions, const_json, parse_with};
+///
+/// let src = "{name: 'app', /* not JSON */ tags: ['a', True,],}";
+/// assert!(parse_with(src, &ParserOptions::strict()).is_err());
+/// assert!(parse_with(src, &ParserOptions::json5()).is_err());
+///
+/// let parsed = parse_with(src, &ParserOptions::lenient()).unwrap();
+/// assert_eq!(parsed, const_json!({"name": "app", "tags": ["a", true]}));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Treat `//` line comments and `/* */` block comments as whitespace, as
+    /// [`parse_jsonc`] does.
+    pub comments: bool,
+    /// Allow a `,` after the last element of an array or object.
+    pub trailing_commas: bool,
+    /// Allow strings, and quoted keys, to be delimited by `'` as well as `"`. Inside them, `\'`
+    /// is a valid escape.
+    pub single_quotes: bool,
+    /// Allow object keys written as bare identifiers, made of ASCII letters, digits, `_` and `$`.
+    pub unquoted_keys: bool,
+    /// Allow the number forms of JSON5: hexadecimal integers such as `0xFF`, a leading or
+    /// trailing decimal point, a leading `+`, and `Infinity` and `NaN`.
+    pub json5_numbers: bool,
+    /// Accept `true`, `false` and `null` in any case, such as `True` or `NULL`, as some lenient
+    /// writers produce. RFC 8259 allows only lowercase, so by default any other case fails with
+    /// [`ParseErrorKind::MiscasedLiteral`].
+    pub case_insensitive_literals: bool,
+    /// What to do with a key that appears more than once in the same object.
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// How many arrays and objects may be nested inside each other before parsing fails with
+    /// [`ParseErrorKind::DepthLimitExceeded`]. Defaults to [`MAX_DEPTH`](Self::MAX_DEPTH).
+    ///
+    /// The parser recurses once per level of nesting, so this is what keeps deeply nested input
+    /// from overflowing the stack. The default is safe on any thread; raise it only as far as the
+    /// stack allows.
+    pub max_depth: usize,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a parser does with a key that appears more than once in the same object, which JSON
+/// allows but does not give a meaning to.
+///
+/// The default is [`KeepAll`](Self::KeepAll) rather than the more common
+/// [`LastWins`](Self::LastWins), because it is the only policy that loses nothing: the parsed
+/// value holds exactly what was written, as [`const_json!`](crate::const_json) does, and costs
+/// nothing extra to build. The other policies look each key up in a map of the object's keys so
+/// far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Keep every entry, in order, as the object was written.
+    #[default]
+    KeepAll,
+    /// Keep only the first entry for each key.
+    FirstWins,
+    /// Keep only one entry for each key, where it first appears, holding the value from where it
+    /// last appears. Most parsers in other languages behave like this.
+    LastWins,
+    /// Fail with [`ParseErrorKind::DuplicateKey`] at the second appearance of a key.
+    Error,
+}
+
+impl ParserOptions {
+    /// The default [`max_depth`](Self::max_depth). [`parse`] and the other parsing functions
+    /// all use this limit.
+    pub const MAX_DEPTH: usize = 128;
+
+    /// Creates options with the defaults, the same as [`strict`](Self::strict).
+    pub const fn new() -> Self {
+        Self {
+            comments: false,
+            trailing_commas: false,
+            single_quotes: false,
+            unquoted_keys: false,
+            json5_numbers: false,
+            case_insensitive_literals: false,
+            duplicate_keys: DuplicateKeyPolicy::KeepAll,
+            max_depth: Self::MAX_DEPTH,
+        }
+    }
+
+    /// Options that accept only RFC 8259 JSON, with every extension off.
+    pub const fn strict() -> Self {
+        Self::new()
+    }
+
+    /// Options that accept [JSON5](https://json5.org), as [`parse_json5`] does: comments,
+    /// trailing commas, single quotes, unquoted keys and the JSON5 number forms.
+    pub const fn json5() -> Self {
+        Self {
+            comments: true,
+            trailing_commas: true,
+            single_quotes: true,
+            unquoted_keys: true,
+            json5_numbers: true,
+            ..Self::new()
+        }
+    }
+
+    /// Options for forgiving hand-written input: everything [`json5`](Self::json5) accepts, as
+    /// well as literals in any case.
+    pub const fn lenient() -> Self {
+        Self {
+            case_insensitive_literals: true,
+            ..Self::json5()
+        }
+    }
+
+    /// Sets [`comments`](Self::comments).
+    ///
+    /// ```rust
+    /// use const_json::{ParserOptions, const_json, parse_with};
+    ///
+    /// let options = ParserOptions::new().comments(true);
+    /// assert_eq!(parse_with("[1, // one\n 2 /* two */]", &options).unwrap(), const_json!([1, 2]));
+    /// assert!(parse_with("[1 /* unterminated", &options).is_err());
+    /// assert!(parse_with("[1, // one\n 2]", &ParserOptions::new()).is_err());
+    /// ```
+    pub const fn comments(self, comments: bool) -> Self {
+        Self { comments, ..self }
+    }
+
+    /// Sets [`trailing_commas`](Self::trailing_commas).
+    ///
+    /// ```rust
+    /// use const_json::{ParserOptions, const_json, parse_with};
+    ///
+    /// let options = ParserOptions::new().trailing_commas(true);
+    /// assert_eq!(parse_with(r#"{"a": [1, 2,],}"#, &options).unwrap(), const_json!({"a": [1, 2]}));
+    /// assert!(parse_with("[1,,]", &options).is_err());
+    /// assert!(parse_with("[,]", &options).is_err());
+    /// assert!(parse_with("[1,]", &ParserOptions::new()).is_err());
+    /// ```
+    pub const fn trailing_commas(self, trailing_commas: bool) -> Self {
+        Self {
+            trailing_commas,
+            ..self
+        }
+    }
+
+    /// Sets [`single_quotes`](Self::single_quotes).
+    ///
+    /// ```rust
+    /// use const_json::{ParserOptions, const_json, parse_with};
+    ///
+    /// let options = ParserOptions::new().single_quotes(true);
+    /// let parsed = parse_with(r#"{'it\'s': "a 'b'"}"#, &options).unwrap();
+    /// assert_eq!(parsed, const_json!({"it's": "a 'b'"}));
+    /// assert!(parse_with("'a'", &ParserOptions::new()).is_err());
+    /// ```
+    pub const fn single_quotes(self, single_quotes: bool) -> Self {
+        Self {
+            single_quotes,
+            ..self
+        }
+    }
+
+    /// Sets [`unquoted_keys`](Self::unquoted_keys).
+    ///
+    /// ```rust
+    /// use const_json::{ParserOptions, const_json, parse_with};
+    ///
+    /// let options = ParserOptions::new().unquoted_keys(true);
+    /// let parsed = parse_with(r#"{name: 1, $id_2: 2, "quoted": 3}"#, &options).unwrap();
+    /// assert_eq!(parsed, const_json!({"name": 1, "$id_2": 2, "quoted": 3}));
+    /// assert!(parse_with("{2x: 1}", &options).is_err());
+    /// assert!(parse_with("{name: 1}", &ParserOptions::new()).is_err());
+    /// ```
+    pub const fn unquoted_keys(self, unquoted_keys: bool) -> Self {
+        Self {
+            unquoted_keys,
+            ..self
+        }
+    }
+
+    /// Sets [`json5_numbers`](Self::json5_numbers).
+    ///
+    /// ```rust
+    /// use const_json::{ParserOptions, const_json, parse_with};
+    ///
+    /// let options = ParserOptions::new().json5_numbers(true);
+    /// assert_eq!(parse_with("[0x1F, .5, +1]", &options).unwrap(), const_json!([31, 0.5, 1]));
+    /// assert!(parse_with("0x1F", &ParserOptions::new()).is_err());
+    /// ```
+    pub const fn json5_numbers(self, json5_numbers: bool) -> Self {
+        Self {
+            json5_numbers,
+            ..self
+        }
+    }
+
+    /// Sets [`case_insensitive_literals`](Self::case_insensitive_literals).
+    ///
+    /// ```rust
+    /// use const_json::{ParseErrorKind, ParserOptions, const_json, parse, parse_with};
+    ///
+    /// let src = "[True, FALSE, Null]";
+    /// let err = parse(src).unwrap_err();
+    /// assert_eq!((&err.kind, err.offset), (&ParseErrorKind::MiscasedLiteral, 1));
+    /// assert!(err.to_string().ends_with("column 2: true, false and null must be lowercase"));
+    /// assert_eq!(parse("[FALSE]").unwrap_err().kind, ParseErrorKind::MiscasedLiteral);
+    /// assert_eq!(parse("[Null]").unwrap_err().kind, ParseErrorKind::MiscasedLiteral);
+    ///
+    /// let lenient = ParserOptions::new().case_insensitive_literals(true);
+    /// assert_eq!(parse_with(src, &lenient).unwrap(), const_json!([true, false, null]));
+    /// let err = parse_with("Nil", &lenient).unwrap_err();
+    /// assert_eq!(err.kind, ParseErrorKind::UnexpectedChar('N'));
+    /// ```
+    pub const fn case_insensitive_literals(self, case_insensitive_literals: bool) -> Self {
+        Self {
+            case_insensitive_literals,
+            ..self
+        }
+    }
+
+    /// Sets [`duplicate_keys`](Self::duplicate_keys).
+    ///
+    /// ```rust
+    /// use const_json::{DuplicateKeyPolicy, ParserOptions, const_json, parse, parse_with};
+    ///
+    /// let src = r#"{"a": 1, "b": 2, "a": 3}"#;
+    /// assert_eq!(parse(src).unwrap(), const_json!({"a": 1, "b": 2, "a": 3}));
+    ///
+    /// let first = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::FirstWins);
+    /// assert_eq!(parse_with(src, &first).unwrap(), const_json!({"a": 1, "b": 2}));
+    ///
+    /// let last = ParserOptions::new().duplicate_keys(DuplicateKeyPolicy::LastWins);
+    /// assert_eq!(parse_with(src, &last).unwrap(), const_json!({"a": 3, "b": 2}));
+    /// ```
+    ///
+    /// With [`DuplicateKeyPolicy::Error`], the error names the repeated key, and its offset is
+    /// where that key starts:
+    ///
+    /// ```rust
+    /// use const_json::{DuplicateKeyPolicy, ParseErrorKind, ParserOptions, const_json, parse_with};
+    ///
+    /// let src = r#"{"a":1,"a":2}"#;
+    /// let policy = |policy| ParserOptions::new().duplicate_keys(policy);
+    /// let first = parse_with(src, &policy(DuplicateKeyPolicy::FirstWins)).unwrap();
+    /// assert_eq!(first, const_json!({"a": 1}));
+    /// let last = parse_with(src, &policy(DuplicateKeyPolicy::LastWins)).unwrap();
+    /// assert_eq!(last, const_json!({"a": 2}));
+    ///
+    /// let err = parse_with(src, &policy(DuplicateKeyPolicy::Error)).unwrap_err();
+    /// assert_eq!((&err.kind, err.offset), (&ParseErrorKind::DuplicateKey("a".into()), 7));
+    /// assert_eq!(&src[err.offset..err.offset + 3], r#""a""#);
+    /// assert_eq!(err.to_string(), "error at line 1, column 8: duplicate key 'a' in object");
+    ///
+    /// // Only keys in the same object count
+    /// let nested = r#"{"a": {"a": 1}, "b": [{"a": 2}, {"a": 3}]}"#;
+    /// assert!(parse_with(nested, &policy(DuplicateKeyPolicy::Error)).is_ok());
+    /// ```
+    pub const fn duplicate_keys(self, duplicate_keys: DuplicateKeyPolicy) -> Self {
+        Self {
+            duplicate_keys,
+            ..self
+        }
+    }
+
+    /// Sets [`max_depth`](Self::max_depth).
+    pub const fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// The extensions to strict JSON that these options turn on.
+    const fn syntax(&self) -> Syntax {
+        Syntax {
+            comments: self.comments,
+            trailing_commas: self.trailing_commas,
+            single_quotes: self.single_quotes,
+            unquoted_keys: self.unquoted_keys,
+            json5_numbers: self.json5_numbers,
+            case_insensitive_literals: self.case_insensitive_literals,
+        }
+    }
+}
+
+/// Parses JSON text into an [`OwnedJson`], accepting the extensions that `options` turn on.
+///
+/// Input nested deeper than [`max_depth`](ParserOptions::max_depth) fails cleanly, however deep
+/// it goes, rather than overflowing the stack:
+///
+/// ```rust
+/// use const_json::{OwnedJson, ParseErrorKind, ParserOptions, parse, parse_with};
+///
+/// let hostile = "[".repeat(1_000_000);
+/// let err = parse(&hostile).unwrap_err();
+/// assert_eq!(err.kind, ParseErrorKind::DepthLimitExceeded);
+/// assert_eq!(err.offset, 128);
+/// assert_eq!(err.to_string(), "error at line 1, column 129: nesting depth limit exceeded");
+///
+/// let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+/// assert!(parse(&nested(128)).is_ok());
+/// assert!(parse(&nested(129)).is_err());
+///
+/// let shallow = ParserOptions::new().max_depth(2);
+/// assert!(parse_with(r#"{"a": [1]}"#, &shallow).is_ok());
+/// let err = parse_with(r#"{"a": [{}]}"#, &shallow).unwrap_err();
+/// assert_eq!((err.kind, err.offset), (ParseErrorKind::DepthLimitExceeded, 7));
+/// assert_eq!(parse_with("1", &ParserOptions::new().max_depth(0)).unwrap(), OwnedJson::Int(1));
+/// ```
+pub fn parse_with(src: &str, options: &ParserOptions) -> Result<OwnedJson, ParseError> {
+    Parser::new(src, Syntax::default())
+        .with_options(options)
+        .parse()
+}
+
+/// Parses strict JSON text into a [`CowJson`], whose strings borrow from `src` wherever they can,
+/// so that a document made mostly of strings without escapes needs far fewer allocations.
+///
+/// A string, or object key, is only copied into a new `String` if it contains an escape sequence
+/// that has to be decoded. Arrays and objects are still collected into vectors.
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use const_json::{CowJson, const_json, parse, parse_borrowed};
+///
+/// let src = r#"{"name": "plain", "quote": "say \"hi\""}"#;
+/// let json = parse_borrowed(src).unwrap();
+/// let CowJson::Object(entries) = &json else { panic!() };
+///
+/// let (key, CowJson::Str(Cow::Borrowed(name))) = &entries[0] else { panic!() };
+/// // Both point straight into `src`
+/// assert_eq!(key.as_ptr(), src[2..].as_ptr());
+/// assert_eq!(name.as_ptr(), src[10..].as_ptr());
+///
+/// let CowJson::Str(Cow::Owned(quote)) = &entries[1].1 else { panic!() };
+/// assert_eq!(quote, "say \"hi\"");
+///
+/// assert_eq!(json.into_owned(), parse(src).unwrap());
+/// ```
+pub fn parse_borrowed(src: &str) -> Result<CowJson<'_>, ParseError> {
+    Parser::new(src, Syntax::default()).parse()
+}
+
+/// Parses strict JSON text into an [`OwnedJson`].
+///
+/// ```rust
+/// use const_json::{Json, const_json, parse};
+///
+/// const EXPECTED: Json = const_json!({"name": "a\tb", "list": [1, 2.5, true, null]});
+///
+/// let parsed = parse(r#"{ "name": "a\tb", "list": [1, 2.5, true, null] }"#).unwrap();
+/// assert_eq!(parsed, EXPECTED);
+///
+/// assert!(parse("[1, 2,]").is_err());
+///
+/// // Strings are checked strictly
+/// use const_json::ParseErrorKind;
+/// let kind = |src| parse(src).unwrap_err().kind;
+/// assert_eq!(kind(r#""\uD834""#), ParseErrorKind::LoneSurrogate);
+/// assert_eq!(kind(r#""\uDD1E\uD834""#), ParseErrorKind::LoneSurrogate);
+/// assert_eq!(kind(r#""\u00G1""#), ParseErrorKind::InvalidUnicodeEscape);
+/// assert_eq!(kind(r#""\uD83D\u12""#), ParseErrorKind::InvalidUnicodeEscape);
+/// assert_eq!(kind(r#""\uD83D\u0041""#), ParseErrorKind::LoneSurrogate);
+/// assert_eq!(kind(r#""\q""#), ParseErrorKind::InvalidEscape);
+/// assert_eq!(kind("\"a\u{1}\""), ParseErrorKind::ControlCharacter);
+/// assert_eq!(parse("\"a\u{1}\"").unwrap_err().offset, 2);
+/// ```
+///
+/// Numbers follow the JSON grammar: an optional `-`, an integer part with no leading zeros, then
+/// optionally a fraction and an exponent, whose `e` may be either case and be followed by a sign.
+/// A number with a fraction or an exponent becomes a [`Float`](OwnedJson::Float), even if its
+/// value is integral, and one without becomes an [`Int`](OwnedJson::Int):
+///
+/// ```rust
+/// use const_json::{OwnedJson, ParseErrorKind, parse};
+///
+/// assert_eq!(parse("1e3").unwrap(), OwnedJson::Float(1000.0));
+/// assert_eq!(parse("1.5E-2").unwrap(), OwnedJson::Float(0.015));
+/// assert_eq!(parse("2e+2").unwrap(), OwnedJson::Float(200.0));
+/// assert_eq!(parse("0.0").unwrap(), OwnedJson::Float(0.0));
+/// assert_eq!(parse("-0").unwrap(), OwnedJson::Int(0));
+/// assert_eq!(parse("-12").unwrap(), OwnedJson::Int(-12));
+///
+/// for invalid in ["01", "-01", "1.", "1e", "1e+", "-", "[00]"] {
+///     assert_eq!(parse(invalid).unwrap_err().kind, ParseErrorKind::InvalidNumber, "{invalid}");
+/// }
+/// // These are not numbers at all, though `parse_json5` accepts them
+/// assert_eq!(parse("+1").unwrap_err().kind, ParseErrorKind::UnexpectedChar('+'));
+/// assert_eq!(parse(".5").unwrap_err().kind, ParseErrorKind::UnexpectedChar('.'));
+/// ```
+///
+/// An integer outside the range of an `i64` never wraps. It becomes the nearest
+/// [`Float`](OwnedJson::Float) instead, losing precision beyond about 16 significant digits, as do
+/// integers in `include_json!` and [`Json::parse_number`]:
+///
+/// ```rust
+/// use const_json::{Json, Number, OwnedJson, parse};
+///
+/// assert_eq!(parse("9223372036854775807").unwrap(), OwnedJson::Int(i64::MAX));
+/// assert_eq!(parse("9223372036854775808").unwrap(), OwnedJson::Float(9223372036854775808.0));
+/// assert_eq!(parse("18446744073709551615").unwrap(), OwnedJson::Float(18446744073709551615.0));
+/// assert_eq!(parse("9999999999999999999").unwrap(), OwnedJson::Float(1e19));
+///
+/// assert_eq!(parse("-9223372036854775808").unwrap(), OwnedJson::Int(i64::MIN));
+/// assert_eq!(parse("-9223372036854775809").unwrap(), OwnedJson::Float(-9223372036854775808.0));
+///
+/// const BIG: Option<Number> = Json::Str("18446744073709551615").parse_number();
+/// assert_eq!(BIG, Some(Number::Float(18446744073709551615.0)));
+/// ```
+pub fn parse(src: &str) -> Result<OwnedJson, ParseError> {
+    Parser::new(src, Syntax::default()).parse()
+}
+
+/// Parses strict JSON straight from bytes, such as those read from a socket.
+///
+/// Only the contents of strings are checked to be UTF-8, since everywhere else the parser only
+/// accepts ASCII anyway, so there is no need to validate the whole input as a `str` beforehand.
+///
+/// ```rust
+/// use const_json::{Json, ParseErrorKind, const_json, from_slice};
+///
+/// const EXPECTED: Json = const_json!({"name": "café"});
+/// assert_eq!(from_slice("{\"name\": \"café\"}".as_bytes()).unwrap(), EXPECTED);
+///
+/// let err = from_slice(b"[\"\xff\"]").unwrap_err();
+/// assert_eq!(err.kind, ParseErrorKind::InvalidUtf8);
+/// assert_eq!(err.offset, 2);
+/// ```
+pub fn from_slice(bytes: &[u8]) -> Result<OwnedJson, ParseError> {
+    Parser::from_slice(bytes, Syntax::default()).parse()
+}
+
+/// Parses JSON text that may contain `//` line comments and `/* */` block comments.
+///
+/// Comments may appear anywhere whitespace is allowed, and are dropped from the result. Inside a
+/// string they are just part of the string.
+///
+/// ```rust
+/// use const_json::{Json, const_json, parse_jsonc};
+///
+/// const EXPECTED: Json = const_json!({"port": 8080, "url": "http://example.com"});
+///
+/// let src = r#"
+/// {
+///     // the port to listen on
+///     "port": 8080, /* or 80 */
+///     "url": "http://example.com"
+/// }
+/// // end of file"#;
+/// assert_eq!(parse_jsonc(src).unwrap(), EXPECTED);
+///
+/// assert!(parse_jsonc("[1 /* unterminated").is_err());
+/// assert!(const_json::parse("// comment\n1").is_err());
+/// ```
+pub fn parse_jsonc(src: &str) -> Result<OwnedJson, ParseError> {
+    parse_with(src, &ParserOptions::new().comments(true))
+}
+
+/// Parses [JSON5](https://json5.org) text, a superset of JSON meant for hand-written files.
+///
+/// On top of JSONC comments (see [`parse_jsonc`]), this accepts:
+/// - strings in single quotes, in which `\'` is a valid escape
+/// - object keys written as bare identifiers, made of ASCII letters, digits, `_` and `$`
+/// - a trailing comma after the last element of an array or object
+/// - hexadecimal integers such as `0xFF`
+/// - numbers with a leading or trailing decimal point, such as `.5` or `5.`
+/// - a leading `+` on numbers, and the numbers `Infinity` and `NaN`
+///
+/// ```rust
+/// use const_json::{Json, const_json, parse_json5};
+///
+/// const EXPECTED: Json = const_json!({"name": "it's", "list": [1, 2]});
+/// assert_eq!(parse_json5("{name: 'it\\'s', list: [1, 2,],}").unwrap(), EXPECTED);
+///
+/// let number = |src| parse_json5(src).unwrap();
+/// assert_eq!(number("0xFF"), const_json!(255));
+/// assert_eq!(number("-0x10"), const_json!(-16));
+/// assert_eq!(number(".5"), const_json!(0.5));
+/// assert_eq!(number("5."), const_json!(5.0));
+/// assert_eq!(number("+1.5e1"), const_json!(15.0));
+/// assert_eq!(number("+7"), const_json!(7));
+/// assert_eq!(number("-Infinity"), const_json!((f64::NEG_INFINITY)));
+/// assert!(matches!(number("NaN"), const_json::OwnedJson::Float(f) if f.is_nan()));
+///
+/// assert!(parse_json5(".").is_err());
+/// assert!(parse_json5("0x").is_err());
+/// assert!(parse_json5("01").is_err());
+/// ```
+pub fn parse_json5(src: &str) -> Result<OwnedJson, ParseError> {
+    parse_with(src, &ParserOptions::json5())
+}
+
+/// Parses [JSON Lines](https://jsonlines.org) (NDJSON) text, in which each line holds one strict
+/// JSON value, into an array of those values.
+///
+/// Lines are ended by `\n` or `\r\n`. Lines that are empty or only whitespace are skipped, so a
+/// trailing newline is fine. A value cannot span lines, and errors give the line and column within
+/// the whole input.
+///
+/// ```rust
+/// use const_json::{Json, const_json, parse_ndjson};
+///
+/// const EXPECTED: Json = const_json!([{"id": 1, "tags": ["a"]}, {"id": 2}, null]);
+/// let src = "{\"id\": 1, \"tags\": [\"a\"]}\r\n{\"id\": 2}\n\n  \nnull\n";
+/// assert_eq!(parse_ndjson(src).unwrap(), EXPECTED);
+/// assert_eq!(parse_ndjson("").unwrap(), const_json!([]));
+///
+/// let err = parse_ndjson("{\"id\": 1}\n{\"id\": 2,}\n").unwrap_err();
+/// assert_eq!((err.line, err.column), (2, 10));
+///
+/// // Each line must be a single, complete value
+/// assert!(parse_ndjson("[1,\n2]").is_err());
+/// assert!(parse_ndjson("1 2").is_err());
+/// ```
+pub fn parse_ndjson(src: &str) -> Result<OwnedJson, ParseError> {
+    let mut values = Vec::new();
+    let mut start = 0;
+    for line in src.split_inclusive('\n') {
+        if !line
+            .bytes()
+            .all(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        {
+            let value = Parser::new(line, Syntax::default())
+                .parse()
+                .map_err(|err| ParseError::new(err.kind, start + err.offset, src.as_bytes()))?;
+            values.push(value);
+        }
+        start += line.len();
+    }
+    Ok(OwnedJson::Array(values))
+}
+
+/// Parses a stream of strict JSON values written one after another, with or without whitespace
+/// between them, as in `{"a":1}{"a":2}`, until the input runs out.
+///
+/// Whitespace is only needed where two values would otherwise run together, as between two
+/// numbers. Errors give the offset into the whole input.
+///
+/// ```rust
+/// use const_json::{Json, OwnedJson, const_json, parse_many};
+///
+/// let values = parse_many(r#"{"a":1}{"a":2}[3]"x"4 5.5 true null"#).unwrap();
+/// const EXPECTED: [Json; 8] = [
+///     const_json!({"a": 1}),
+///     const_json!({"a": 2}),
+///     const_json!([3]),
+///     const_json!("x"),
+///     const_json!(4),
+///     const_json!(5.5),
+///     const_json!(true),
+///     const_json!(null),
+/// ];
+/// assert_eq!(values, EXPECTED);
+///
+/// assert_eq!(parse_many(" \n").unwrap(), Vec::<OwnedJson>::new());
+///
+/// let err = parse_many(r#"[1] {"a" 2}"#).unwrap_err();
+/// assert_eq!(err.offset, 9);
+/// ```
+pub fn parse_many(src: &str) -> Result<Vec<OwnedJson>, ParseError> {
+    let mut parser = Parser::new(src, Syntax::default());
+    let mut values = Vec::new();
+    loop {
+        parser.reader.skip_whitespace()?;
+        if parser.reader.peek().is_none() {
+            return Ok(values);
+        }
+        values.push(parser.value()?);
+    }
+}
+
+/// Builds an [`OwnedJson`] out of the tokens recognised by a [`Reader`].
+struct Parser<'s> {
+    reader: Reader<'s>,
+    /// The input, if it is already known to be UTF-8.
+    src: Option<&'s str>,
+    /// How many more arrays and objects may be opened inside the current one.
+    depth: usize,
+    duplicate_keys: DuplicateKeyPolicy,
+}
+
+impl<'s> Parser<'s> {
+    fn new(src: &'s str, syntax: Syntax) -> Self {
+        Self {
+            reader: Reader::new(src.as_bytes(), syntax),
+            src: Some(src),
+            depth: ParserOptions::MAX_DEPTH,
+            duplicate_keys: DuplicateKeyPolicy::KeepAll,
+        }
+    }
+
+    fn from_slice(src: &'s [u8], syntax: Syntax) -> Self {
+        Self {
+            reader: Reader::new(src, syntax),
+            src: None,
+            depth: ParserOptions::MAX_DEPTH,
+            duplicate_keys: DuplicateKeyPolicy::KeepAll,
+        }
+    }
+
+    fn with_options(mut self, options: &ParserOptions) -> Self {
+        self.reader.syntax = options.syntax();
+        Self {
+            depth: options.max_depth,
+            duplicate_keys: options.duplicate_keys,
+            ..self
+        }
+    }
+
+    /// Gets the text of the input between `start` and the cursor.
+    fn text(&self, start: usize) -> Result<&'s str, ParseError> {
+        let end = self.reader.pos;
+        match self.src {
+            Some(src) => Ok(&src[start..end]),
+            None => core::str::from_utf8(&self.reader.src[start..end]).map_err(|e| {
+                let offset = start + e.valid_up_to();
+                ParseError::new(ParseErrorKind::InvalidUtf8, offset, self.reader.src)
+            }),
+        }
+    }
+
+    fn parse<V: Build<'s>>(mut self) -> Result<V, ParseError> {
+        let value = self.value()?;
+        self.reader.finish()?;
+        Ok(value)
+    }
+
+    fn value<V: Build<'s>>(&mut self) -> Result<V, ParseError> {
+        self.reader.skip_whitespace()?;
+        Ok(match self.reader.peek() {
+            Some(b'{') => self.nested(Self::object)?,
+            Some(b'[') => self.nested(Self::array)?,
+            Some(b'"') => V::str(self.string()?),
+            Some(b'\'') if self.reader.syntax.single_quotes => V::str(self.string()?),
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            Some(b'N')
+                if self.reader.syntax.json5_numbers
+                    && self.reader.src[self.reader.pos..].starts_with(b"NaN") =>
+            {
+                self.number()?
+            }
+            Some(b'+' | b'.' | b'I') if self.reader.syntax.json5_numbers => self.number()?,
+            Some(b't' | b'T') => {
+                self.reader.literal(b"true")?;
+                V::bool(true)
+            }
+            Some(b'f' | b'F') => {
+                self.reader.literal(b"false")?;
+                V::bool(false)
+            }
+            Some(b'n' | b'N') => {
+                self.reader.literal(b"null")?;
+                V::null()
+            }
+            _ => return Err(self.reader.unexpected()),
+        })
+    }
+
+    /// Parses an array or object with `parse`, one level deeper.
+    fn nested<V: Build<'s>>(
+        &mut self,
+        parse: fn(&mut Self) -> Result<V, ParseError>,
+    ) -> Result<V, ParseError> {
+        if self.depth == 0 {
+            return Err(self.reader.error(ParseErrorKind::DepthLimitExceeded));
+        }
+        self.depth -= 1;
+        let value = parse(self);
+        self.depth += 1;
+        value
+    }
+
+    fn object<V: Build<'s>>(&mut self) -> Result<V, ParseError> {
+        self.reader.expect(b'{')?;
+        let mut entries = Vec::new();
+        // The index in `entries` of each key, unless every entry is kept anyway
+        let mut index = BTreeMap::new();
+        self.reader.skip_whitespace()?;
+        if self.reader.peek() == Some(b'}') {
+            self.reader.pos += 1;
+            return Ok(V::object(entries));
+        }
+        loop {
+            self.reader.skip_whitespace()?;
+            let key_at = self.reader.pos;
+            let key = self.key()?;
+            self.reader.skip_whitespace()?;
+            self.reader.expect(b':')?;
+            let value = self.value()?;
+            self.insert(&mut entries, &mut index, key, key_at, value)?;
+            self.reader.skip_whitespace()?;
+            match self.reader.peek() {
+                Some(b',') => self.reader.pos += 1,
+                Some(b'}') => {
+                    self.reader.pos += 1;
+                    return Ok(V::object(entries));
+                }
+                _ => return Err(self.reader.unexpected()),
+            }
+            if self.trailing_comma(b'}')? {
+                return Ok(V::object(entries));
+            }
+        }
+    }
+
+    /// Adds an entry, whose key starts at `key_at`, to `entries`, following the
+    /// [`DuplicateKeyPolicy`]. `index` maps the keys of `entries` to their positions.
+    fn insert<V: Build<'s>>(
+        &self,
+        entries: &mut Vec<(V::Key, V)>,
+        index: &mut BTreeMap<Cow<'s, str>, usize>,
+        key: Cow<'s, str>,
+        key_at: usize,
+        value: V,
+    ) -> Result<(), ParseError> {
+        let policy = self.duplicate_keys;
+        if policy == DuplicateKeyPolicy::KeepAll {
+            entries.push((V::key(key), value));
+            return Ok(());
+        }
+        match index.get(key.as_ref()) {
+            Some(&idx) => match policy {
+                DuplicateKeyPolicy::LastWins => entries[idx].1 = value,
+                DuplicateKeyPolicy::Error => {
+                    let kind = ParseErrorKind::DuplicateKey(key.into_owned());
+                    return Err(ParseError::new(kind, key_at, self.reader.src));
+                }
+                _ => {}
+            },
+            None => {
+                index.insert(key.clone(), entries.len());
+                entries.push((V::key(key), value));
+            }
+        }
+        Ok(())
+    }
+
+    fn array<V: Build<'s>>(&mut self) -> Result<V, ParseError> {
+        self.reader.expect(b'[')?;
+        let mut elements = Vec::new();
+        self.reader.skip_whitespace()?;
+        if self.reader.peek() == Some(b']') {
+            self.reader.pos += 1;
+            return Ok(V::array(elements));
+        }
+        loop {
+            elements.push(self.value()?);
+            self.reader.skip_whitespace()?;
+            match self.reader.peek() {
+                Some(b',') => self.reader.pos += 1,
+                Some(b']') => {
+                    self.reader.pos += 1;
+                    return Ok(V::array(elements));
+                }
+                _ => return Err(self.reader.unexpected()),
+            }
+            if self.trailing_comma(b']')? {
+                return Ok(V::array(elements));
+            }
+        }
+    }
+
+    /// After a `,`, consumes `close` if it follows and trailing commas are allowed.
+    fn trailing_comma(&mut self, close: u8) -> Result<bool, ParseError> {
+        if !self.reader.syntax.trailing_commas {
+            return Ok(false);
+        }
+        self.reader.skip_whitespace()?;
+        if self.reader.peek() == Some(close) {
+            self.reader.pos += 1;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn number<V: Build<'s>>(&mut self) -> Result<V, ParseError> {
+        let start = self.reader.pos;
+        self.reader.number()?;
+        let text = self.text(start)?;
+
+        if self.reader.syntax.json5_numbers {
+            let (negative, unsigned) = match text.as_bytes()[0] {
+                b'-' => (true, &text[1..]),
+                b'+' => (false, &text[1..]),
+                _ => (false, text),
+            };
+            let sign = if negative { -1.0 } else { 1.0 };
+            if unsigned == "Infinity" {
+                return Ok(V::float(sign * f64::INFINITY));
+            } else if unsigned == "NaN" {
+                return Ok(V::float(f64::NAN));
+            } else if let Some(hex) = unsigned
+                .strip_prefix("0x")
+                .or_else(|| unsigned.strip_prefix("0X"))
+            {
+                let magnitude = u64::from_str_radix(hex, 16)
+                    .ok()
+                    .and_then(|m| i64::try_from(m).ok());
+                return Ok(match magnitude {
+                    Some(m) => V::int(if negative { -m } else { m }),
+                    None => V::float(
+                        sign * hex.bytes().fold(0.0, |acc, b| {
+                            acc * 16.0 + (b as char).to_digit(16).unwrap_or(0) as f64
+                        }),
+                    ),
+                });
+            }
+        }
+
+        // Anything too large for an `i64` falls through to a float
+        if !text.bytes().any(|b| matches!(b, b'.' | b'e' | b'E'))
+            && let Ok(int) = text.parse()
+        {
+            return Ok(V::int(int));
+        }
+        // The reader has already checked the text against the number grammar, which is a subset
+        // of what `f64::from_str` accepts
+        Ok(V::float(text.parse().unwrap_or(f64::NAN)))
+    }
+
+    fn key(&mut self) -> Result<Cow<'s, str>, ParseError> {
+        match self.reader.peek() {
+            Some(b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$') if self.reader.syntax.unquoted_keys => {
+                let start = self.reader.pos;
+                self.reader.identifier()?;
+                Ok(Cow::Borrowed(self.text(start)?))
+            }
+            _ => self.string(),
+        }
+    }
+
+    /// Reads a string, borrowing its contents from the input unless they contain escapes.
+    fn string(&mut self) -> Result<Cow<'s, str>, ParseError> {
+        let start = self.reader.pos;
+        self.reader.string()?;
+        let token = self.text(start)?;
+        let content = &token[1..token.len() - 1];
+        if !content.contains('\\') {
+            return Ok(Cow::Borrowed(content));
+        }
+        unescape(content, self.reader.syntax.single_quotes)
+            .map(Cow::Owned)
+            .map_err(|(kind, offset)| ParseError::new(kind, start + 1 + offset, self.reader.src))
+    }
+}
+
+/// A value that a [`Parser`] can build, with strings that may borrow from the input.
+trait Build<'s>: Sized {
+    /// The type of object keys.
+    type Key;
+
+    fn null() -> Self;
+    fn bool(b: bool) -> Self;
+    fn int(i: i64) -> Self;
+    fn float(f: f64) -> Self;
+    fn str(s: Cow<'s, str>) -> Self;
+    fn key(s: Cow<'s, str>) -> Self::Key;
+    fn array(elems: Vec<Self>) -> Self;
+    fn object(entries: Vec<(Self::Key, Self)>) -> Self;
+}
+
+impl<'s> Build<'s> for OwnedJson {
+    type Key = String;
+
+    fn null() -> Self {
+        Self::Null(())
+    }
+
+    fn bool(b: bool) -> Self {
+        Self::Bool(b)
+    }
+
+    fn int(i: i64) -> Self {
+        Self::Int(i)
+    }
+
+    fn float(f: f64) -> Self {
+        Self::Float(f)
+    }
+
+    fn str(s: Cow<'s, str>) -> Self {
+        Self::Str(s.into_owned())
+    }
+
+    fn key(s: Cow<'s, str>) -> String {
+        s.into_owned()
+    }
+
+    fn array(elems: Vec<Self>) -> Self {
+        Self::Array(elems)
+    }
+
+    fn object(entries: Vec<(String, Self)>) -> Self {
+        Self::Object(entries)
+    }
+}
+
+impl<'s> Build<'s> for CowJson<'s> {
+    type Key = Cow<'s, str>;
+
+    fn null() -> Self {
+        Self::Null(())
+    }
+
+    fn bool(b: bool) -> Self {
+        Self::Bool(b)
+    }
+
+    fn int(i: i64) -> Self {
+        Self::Int(i)
+    }
+
+    fn float(f: f64) -> Self {
+        Self::Float(f)
+    }
+
+    fn str(s: Cow<'s, str>) -> Self {
+        Self::Str(s)
+    }
+
+    fn key(s: Cow<'s, str>) -> Cow<'s, str> {
+        s
+    }
+
+    fn array(elems: Vec<Self>) -> Self {
+        Self::Array(elems)
+    }
+
+    fn object(entries: Vec<(Cow<'s, str>, Self)>) -> Self {
+        Self::Object(entries)
+    }
+}
@@ -0,0 +1,475 @@
+//! Describing the changes between documents, and applying them.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::slice;
+
+use crate::pointer::push_token;
+use crate::{Json, OwnedJson, PointerError};
+
+/// Computes the smallest JSON Merge Patch, as defined by RFC 7386, that turns `from` into `to`.
+///
+/// For two objects, the patch is an object with an entry for each key whose value differs: `null`
+/// for a key that `to` lacks, a patch computed the same way for two objects, and the value from
+/// `to` for anything else. Any other pair of values, including two arrays, gives `to` itself,
+/// since a merge patch can only replace them wholesale. Values are compared with the derived
+/// [`PartialEq`], so `1` and `1.0` differ.
+///
+/// A merge patch cannot set a value to `null`, since `null` removes a key instead, so a key of
+/// `to` whose value is `null` only round trips if `from` lacks it or already has `null` there.
+/// The same goes for `null`s inside objects that replace other values.
+///
+/// ```rust
+/// use const_json::{Json, OwnedJson, apply_merge_patch, const_json, merge_patch_diff};
+///
+/// const FROM: Json = const_json!({
+///     "title": "Hello",
+///     "author": {"name": "Ann", "email": "a@x"},
+///     "tags": [1, 2]
+/// });
+/// const TO: Json = const_json!({"title": "Hi", "author": {"name": "Ann"}, "tags": [1], "x": 0});
+/// const LIST: Json = const_json!([1]);
+/// const ONE: Json = const_json!(1);
+///
+/// let patch = merge_patch_diff(&FROM, &TO);
+/// assert_eq!(patch, const_json!({
+///     "title": "Hi", "author": {"email": null}, "tags": [1], "x": 0
+/// }));
+///
+/// for (from, to) in [(FROM, TO), (TO, FROM), (FROM, FROM), (FROM, LIST), (ONE, TO)] {
+///     let mut patched = OwnedJson::from(from);
+///     apply_merge_patch(&mut patched, &merge_patch_diff(&from, &to));
+///     assert_eq!(patched, to);
+/// }
+/// assert_eq!(merge_patch_diff(&FROM, &FROM), const_json!({}));
+/// ```
+pub fn merge_patch_diff(from: &Json<'_>, to: &Json<'_>) -> OwnedJson {
+    let (Json::Object(from), Json::Object(to)) = (*from, *to) else {
+        return to.into();
+    };
+    let to_keys: BTreeSet<&str> = to.iter().map(|&(key, _)| key).collect();
+    // The value of the first entry with each key
+    let mut from_values = BTreeMap::new();
+    for (key, value) in from {
+        from_values.entry(*key).or_insert(value);
+    }
+    let mut patch = Vec::new();
+    for (key, _) in from {
+        if !to_keys.contains(key) {
+            patch.push(((*key).into(), OwnedJson::Null(())));
+        }
+    }
+    for (key, to) in to {
+        match from_values.get(key) {
+            Some(&from) if from == to => {}
+            Some(&from) => patch.push(((*key).into(), merge_patch_diff(from, to))),
+            None => patch.push(((*key).into(), to.into())),
+        }
+    }
+    OwnedJson::Object(patch)
+}
+
+/// Applies a JSON Merge Patch, as defined by RFC 7386, to `target`.
+///
+/// If `patch` is an object, each of its entries changes the key of the same name in `target`,
+/// which is made an empty object first if it is not one: `null` removes the key, and anything
+/// else is merged into the key's value the same way, or added if the key is missing. Any other
+/// `patch` replaces `target`.
+///
+/// ```rust
+/// use const_json::{OwnedJson, apply_merge_patch, const_json};
+///
+/// let mut doc = OwnedJson::from(const_json!({"a": "b", "c": {"d": "e", "f": "g"}}));
+/// let patch = OwnedJson::from(const_json!({"a": "z", "c": {"f": null}}));
+/// apply_merge_patch(&mut doc, &patch);
+/// assert_eq!(doc, const_json!({"a": "z", "c": {"d": "e"}}));
+/// ```
+pub fn apply_merge_patch(target: &mut OwnedJson, patch: &OwnedJson) {
+    let OwnedJson::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !matches!(target, OwnedJson::Object(_)) {
+        *target = OwnedJson::Object(Vec::new());
+    }
+    let OwnedJson::Object(entries) = target else {
+        unreachable!()
+    };
+    // The index of the first remaining entry with each key, and for each entry, the index of
+    // the next one with the same key, which takes its place if it is removed
+    let mut first = BTreeMap::new();
+    let mut next_same = vec![None; entries.len()];
+    for (idx, (key, _)) in entries.iter().enumerate().rev() {
+        next_same[idx] = first.insert(key.clone(), idx);
+    }
+    let mut removed = vec![false; entries.len()];
+    for (key, value) in patch {
+        let existing = first.get(key).copied();
+        match (existing, value) {
+            (Some(idx), OwnedJson::Null(())) => {
+                removed[idx] = true;
+                match next_same[idx] {
+                    Some(next) => first.insert(key.clone(), next),
+                    None => first.remove(key),
+                };
+            }
+            (None, OwnedJson::Null(())) => {}
+            (Some(idx), value) => apply_merge_patch(&mut entries[idx].1, value),
+            (None, value) => {
+                let mut new = OwnedJson::Null(());
+                apply_merge_patch(&mut new, value);
+                first.insert(key.clone(), entries.len());
+                next_same.push(None);
+                removed.push(false);
+                entries.push((key.clone(), new));
+            }
+        }
+    }
+    let mut idx = 0;
+    entries.retain(|_| {
+        idx += 1;
+        !removed[idx - 1]
+    });
+}
+
+/// One operation of a JSON Patch, as defined by RFC 6902. Paths are JSON Pointers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchOp {
+    /// Adds `value` at `path`, replacing an existing entry of an object or inserting into an array.
+    Add {
+        /// Where to add the value.
+        path: String,
+        /// The value to add.
+        value: OwnedJson,
+    },
+    /// Removes the value at `path`, which must exist.
+    Remove {
+        /// The value to remove.
+        path: String,
+    },
+    /// Replaces the value at `path`, which must exist.
+    Replace {
+        /// The value to replace.
+        path: String,
+        /// The value to put in its place.
+        value: OwnedJson,
+    },
+    /// Removes the value at `from` and adds it at `path`.
+    Move {
+        /// The value to move.
+        from: String,
+        /// Where to add it.
+        path: String,
+    },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy {
+        /// The value to copy.
+        from: String,
+        /// Where to add the copy.
+        path: String,
+    },
+    /// Checks that the value at `path` equals `value`, failing the patch otherwise. Values are
+    /// compared with the derived [`PartialEq`], so `1` and `1.0` differ.
+    Test {
+        /// The value to check.
+        path: String,
+        /// The value it must equal.
+        value: OwnedJson,
+    },
+}
+
+/// The error from [`apply_patch`], saying which operation failed and why.
+///
+/// ```rust
+/// use const_json::{
+///     OwnedJson, PatchErrorKind, PatchOp, PointerError, PointerErrorKind, apply_patch, const_json,
+/// };
+///
+/// let mut doc = OwnedJson::from(const_json!({"a": {"b": 1}}));
+/// let ops = [
+///     PatchOp::Add { path: "/b".into(), value: const_json!(2).into() },
+///     PatchOp::Remove { path: "/a/c".into() },
+/// ];
+///
+/// let err = apply_patch(&mut doc, &ops).unwrap_err();
+/// let missing = PointerError { kind: PointerErrorKind::MissingKey, offset: 2 };
+/// assert_eq!((err.index, err.kind), (1, PatchErrorKind::Pointer(missing)));
+/// assert_eq!(
+///     err.to_string(),
+///     "operation 1 failed: pointer token names a missing key, at byte 2 of the pointer",
+/// );
+///
+/// let ops = [PatchOp::Copy { from: "/a/b/0".into(), path: "/c".into() }];
+/// let err = apply_patch(&mut doc, &ops).unwrap_err();
+/// let scalar = PointerError { kind: PointerErrorKind::NotContainer, offset: 4 };
+/// assert_eq!(err.kind, PatchErrorKind::FromPointer(scalar));
+/// // Nothing was applied
+/// assert_eq!(doc, const_json!({"a": {"b": 1}}));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PatchError {
+    /// What went wrong.
+    pub kind: PatchErrorKind,
+    /// The index of the operation that failed.
+    pub index: usize,
+}
+
+/// The reason a [`PatchError`] was raised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatchErrorKind {
+    /// A `path` is not a valid pointer, or does not lead where the operation needs.
+    Pointer(PointerError),
+    /// The `from` of a [`PatchOp::Move`] or [`PatchOp::Copy`] is not a valid pointer, or does not
+    /// lead to a value.
+    FromPointer(PointerError),
+    /// A [`PatchOp::Test`] found a different value.
+    TestFailed,
+    /// A [`PatchOp::Move`] would move a value inside itself.
+    MoveIntoChild,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "operation {} failed: {}", self.index, self.kind)
+    }
+}
+
+impl fmt::Display for PatchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pointer(err) => fmt::Display::fmt(err, f),
+            Self::FromPointer(err) => write!(f, "in `from`: {err}"),
+            Self::TestFailed => f.write_str("test found a different value"),
+            Self::MoveIntoChild => f.write_str("cannot move a value inside itself"),
+        }
+    }
+}
+
+/// Computes a JSON Patch, as defined by RFC 6902, that turns `from` into `to`, using only
+/// [`Add`](PatchOp::Add), [`Remove`](PatchOp::Remove) and [`Replace`](PatchOp::Replace).
+///
+/// Two objects are compared key by key: keys that `to` lacks are removed, keys in both are
+/// compared the same way, and new keys are added. Two arrays are compared index by index up to the
+/// shorter length, then elements past the end of `to` are removed from the last one down, or
+/// elements past the end of `from` are added in order. Any other pair of values that differ is
+/// replaced. Values are compared with the derived [`PartialEq`], so `1` and `1.0` differ. A key
+/// repeated in `from` is compared using its first entry.
+///
+/// ```rust
+/// use const_json::{Json, OwnedJson, PatchOp, apply_patch, const_json, patch_diff};
+///
+/// const FROM: Json = const_json!({"name": "app", "a/b": [1, 2, 3], "old": true});
+/// const TO: Json = const_json!({"name": "api", "a/b": [1, 5], "m~n": null});
+///
+/// let ops = patch_diff(&FROM, &TO);
+/// assert_eq!(ops, [
+///     PatchOp::Remove { path: "/old".into() },
+///     PatchOp::Replace { path: "/name".into(), value: const_json!("api").into() },
+///     PatchOp::Replace { path: "/a~1b/1".into(), value: const_json!(5).into() },
+///     PatchOp::Remove { path: "/a~1b/2".into() },
+///     PatchOp::Add { path: "/m~0n".into(), value: OwnedJson::Null(()) },
+/// ]);
+///
+/// const LIST: Json = const_json!([{"id": 1}]);
+/// const LONGER: Json = const_json!([{"id": 2}, [], "x"]);
+/// let pairs = [(FROM, TO), (TO, FROM), (FROM, FROM), (LIST, LONGER), (LONGER, LIST), (FROM, LIST)];
+/// for (from, to) in pairs {
+///     let mut patched = OwnedJson::from(from);
+///     apply_patch(&mut patched, &patch_diff(&from, &to)).unwrap();
+///     assert_eq!(patched, to);
+/// }
+/// assert!(patch_diff(&FROM, &FROM).is_empty());
+/// ```
+pub fn patch_diff(from: &Json<'_>, to: &Json<'_>) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    let mut path = String::new();
+    // The containers being compared, innermost last, so very deep values cannot overflow the call
+    // stack
+    let mut stack = Vec::new();
+    diff_into(&mut ops, &mut stack, &path, from, to);
+    while let Some(frame) = stack.last_mut() {
+        let (from, to) = match frame {
+            DiffFrame::Object { from, to, len } => {
+                path.truncate(*len);
+                let Some((key, to)) = to.next() else {
+                    stack.pop();
+                    continue;
+                };
+                push_token(&mut path, key);
+                match from.get(key) {
+                    Some(&from) => (from, to),
+                    None => {
+                        ops.push(PatchOp::Add {
+                            path: path.clone(),
+                            value: to.into(),
+                        });
+                        continue;
+                    }
+                }
+            }
+            DiffFrame::Array {
+                from,
+                to,
+                next,
+                len,
+            } => {
+                path.truncate(*len);
+                let (from, to, idx) = (*from, *to, *next);
+                if idx < from.len().min(to.len()) {
+                    *next += 1;
+                    path.push_str(&alloc::format!("/{idx}"));
+                    (&from[idx], &to[idx])
+                } else {
+                    stack.pop();
+                    for idx in (to.len()..from.len()).rev() {
+                        ops.push(PatchOp::Remove {
+                            path: alloc::format!("{path}/{idx}"),
+                        });
+                    }
+                    for (idx, to) in to.iter().enumerate().skip(from.len()) {
+                        ops.push(PatchOp::Add {
+                            path: alloc::format!("{path}/{idx}"),
+                            value: to.into(),
+                        });
+                    }
+                    continue;
+                }
+            }
+        };
+        diff_into(&mut ops, &mut stack, &path, from, to);
+    }
+    ops
+}
+
+/// Two containers being compared by [`patch_diff`].
+enum DiffFrame<'j, 'a> {
+    Object {
+        /// The value of the first entry with each key of the old object.
+        from: BTreeMap<&'a str, &'j Json<'a>>,
+        /// The entries of the new object that are left to compare.
+        to: slice::Iter<'j, (&'a str, Json<'a>)>,
+        /// The length of the path to the objects.
+        len: usize,
+    },
+    Array {
+        from: &'j [Json<'a>],
+        to: &'j [Json<'a>],
+        /// The index of the next pair of elements to compare.
+        next: usize,
+        /// The length of the path to the arrays.
+        len: usize,
+    },
+}
+
+/// Starts turning `from`, at `path`, into `to`: pushes the operations for it straight away if
+/// they are not arrays or objects on both sides, and otherwise pushes a frame to compare their
+/// contents, after the removals of keys that only `from` has.
+fn diff_into<'j, 'a>(
+    ops: &mut Vec<PatchOp>,
+    stack: &mut Vec<DiffFrame<'j, 'a>>,
+    path: &str,
+    from: &'j Json<'a>,
+    to: &'j Json<'a>,
+) {
+    match (*from, *to) {
+        (Json::Object(from), Json::Object(to)) => {
+            let to_keys: BTreeSet<&str> = to.iter().map(|&(key, _)| key).collect();
+            let mut from_values = BTreeMap::new();
+            for (key, value) in from {
+                from_values.entry(*key).or_insert(value);
+                if !to_keys.contains(key) {
+                    let mut path = String::from(path);
+                    push_token(&mut path, key);
+                    ops.push(PatchOp::Remove { path });
+                }
+            }
+            stack.push(DiffFrame::Object {
+                from: from_values,
+                to: to.iter(),
+                len: path.len(),
+            });
+        }
+        (Json::Array(from), Json::Array(to)) => stack.push(DiffFrame::Array {
+            from,
+            to,
+            next: 0,
+            len: path.len(),
+        }),
+        // At least one side is a scalar, so this does not recurse
+        _ if from == to => {}
+        _ => ops.push(PatchOp::Replace {
+            path: path.into(),
+            value: to.into(),
+        }),
+    }
+}
+
+/// Applies a JSON Patch, as defined by RFC 6902, to `target`.
+///
+/// The operations are applied in order. If one fails, the error says which, and `target` is left
+/// as it was before any of them.
+///
+/// ```rust
+/// use const_json::{OwnedJson, PatchOp, apply_patch, const_json};
+///
+/// let mut doc = OwnedJson::from(const_json!({"tags": ["a", "c"], "draft": {"title": "Hi"}}));
+/// apply_patch(&mut doc, &[
+///     PatchOp::Test { path: "/tags/0".into(), value: const_json!("a").into() },
+///     PatchOp::Add { path: "/tags/1".into(), value: const_json!("b").into() },
+///     PatchOp::Add { path: "/tags/-".into(), value: const_json!("d").into() },
+///     PatchOp::Move { from: "/draft".into(), path: "/post".into() },
+///     PatchOp::Copy { from: "/post/title".into(), path: "/name".into() },
+///     PatchOp::Replace { path: "/post/title".into(), value: const_json!("Hello").into() },
+///     PatchOp::Remove { path: "/tags/3".into() },
+/// ]).unwrap();
+/// assert_eq!(doc, const_json!({"tags": ["a", "b", "c"], "post": {"title": "Hello"}, "name": "Hi"}));
+/// ```
+pub fn apply_patch(target: &mut OwnedJson, ops: &[PatchOp]) -> Result<(), PatchError> {
+    let mut patched = target.clone();
+    for (index, op) in ops.iter().enumerate() {
+        apply_op(&mut patched, op).map_err(|kind| PatchError { kind, index })?;
+    }
+    *target = patched;
+    Ok(())
+}
+
+/// Applies a single operation to `target`, which may be left partly changed on error.
+fn apply_op(target: &mut OwnedJson, op: &PatchOp) -> Result<(), PatchErrorKind> {
+    match op {
+        PatchOp::Add { path, value } => target.add_pointer(path, value.clone()),
+        PatchOp::Remove { path } => target.remove_pointer(path).map(drop),
+        PatchOp::Replace { path, value } => target
+            .resolve_pointer_mut(path)
+            .map(|old| *old = value.clone()),
+        PatchOp::Move { from, path } => {
+            // A value can move to itself, but not to somewhere inside itself
+            if path
+                .strip_prefix(from.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+            {
+                return Err(PatchErrorKind::MoveIntoChild);
+            }
+            match target.remove_pointer(from) {
+                Ok(value) => target.add_pointer(path, value),
+                Err(err) => return Err(PatchErrorKind::FromPointer(err)),
+            }
+        }
+        PatchOp::Copy { from, path } => match target.resolve_pointer_mut(from) {
+            Ok(value) => {
+                let value = value.clone();
+                target.add_pointer(path, value)
+            }
+            Err(err) => return Err(PatchErrorKind::FromPointer(err)),
+        },
+        PatchOp::Test { path, value } => match target.resolve_pointer_mut(path) {
+            Ok(found) if found == value => Ok(()),
+            Ok(_) => return Err(PatchErrorKind::TestFailed),
+            Err(err) => Err(err),
+        },
+    }
+    .map_err(PatchErrorKind::Pointer)
+}
@@ -0,0 +1,91 @@
+//! The helpers behind [`json_path`](crate::json_path) and
+//! [`try_json_path`](crate::try_json_path).
+//!
+//! Each path segment is wrapped in a [`PathSegment`], whose inherent methods pick an object lookup
+//! for a `&str` and an array lookup for a `usize`. Inherent methods, unlike trait methods, can be
+//! called in a `const` context.
+
+use crate::Json;
+
+/// One segment of a path: a key for `&str`, or an index for `usize`.
+#[doc(hidden)]
+pub struct PathSegment<T>(pub T);
+
+impl PathSegment<&str> {
+    pub const fn get<'j, 'a>(&self, json: &'j Json<'a>) -> &'j Json<'a> {
+        json.get_val(self.0)
+    }
+
+    pub const fn try_get<'j, 'a>(&self, json: &'j Json<'a>) -> Option<&'j Json<'a>> {
+        if let Json::Object(obj) = json {
+            let mut idx = 0;
+            while idx < obj.len() {
+                let (k, v) = &obj[idx];
+                if Json::string_eq(k, self.0) {
+                    return Some(v);
+                }
+                idx += 1;
+            }
+        }
+        None
+    }
+}
+
+impl PathSegment<usize> {
+    pub const fn get<'j, 'a>(&self, json: &'j Json<'a>) -> &'j Json<'a> {
+        json.get_idx(self.0)
+    }
+
+    pub const fn try_get<'j, 'a>(&self, json: &'j Json<'a>) -> Option<&'j Json<'a>> {
+        match json {
+            Json::Array(arr) if self.0 < arr.len() => Some(&arr[self.0]),
+            _ => None,
+        }
+    }
+}
+
+/// The type selectors, each converting the value at the end of a path if it has the right
+/// variant.
+#[doc(hidden)]
+pub struct PathValue;
+
+impl PathValue {
+    pub const fn json<'j, 'a>(json: &'j Json<'a>) -> Option<&'j Json<'a>> {
+        Some(json)
+    }
+
+    pub const fn null(json: &Json<'_>) -> Option<()> {
+        match *json {
+            Json::Null(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub const fn bool(json: &Json<'_>) -> Option<bool> {
+        match *json {
+            Json::Bool(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub const fn float(json: &Json<'_>) -> Option<f64> {
+        match json.number() {
+            Some(number) => Some(number.as_f64()),
+            None => None,
+        }
+    }
+
+    pub const fn int(json: &Json<'_>) -> Option<i64> {
+        match *json {
+            Json::Int(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub const fn str<'j>(json: &'j Json<'_>) -> Option<&'j str> {
+        match *json {
+            Json::Str(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
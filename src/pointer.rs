@@ -0,0 +1,835 @@
+//! JSON Pointers, as defined by RFC 6901, such as `/servers/0/host`.
+//!
+//! A pointer is a sequence of reference tokens, each preceded by a `/`. Inside a token, `~1`
+//! stands for a `/` and `~0` for a `~`. Tokens are kept escaped, and compared against keys by
+//! decoding on the fly, so nothing needs to be allocated.
+
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::Json;
+#[cfg(feature = "alloc")]
+use crate::OwnedJson;
+use crate::include::Message;
+
+/// An error encountered while following a JSON Pointer, saying where in the pointer it went wrong.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{OwnedJson, PointerErrorKind, const_json};
+///
+/// let mut json = OwnedJson::from(const_json!({"servers": [{"host": "a"}]}));
+/// let err = json.set_pointer("/servers/0/host/name", OwnedJson::Null(())).unwrap_err();
+/// assert_eq!((err.kind, err.offset), (PointerErrorKind::NotContainer, 15));
+/// assert_eq!(&"/servers/0/host/name"[err.offset..], "/name");
+/// assert_eq!(
+///     err.to_string(),
+///     "pointer token applied to a non-container value, at byte 15 of the pointer",
+/// );
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PointerError {
+    /// What went wrong.
+    pub kind: PointerErrorKind,
+    /// The byte offset into the pointer of the `/` starting the token that failed, or for
+    /// [`PointerErrorKind::Syntax`], of the first byte that breaks the syntax.
+    pub offset: usize,
+}
+
+/// The reason a [`PointerError`] was raised.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PointerErrorKind {
+    /// The pointer is not empty and does not start with `/`, or it contains a `~` that is not
+    /// followed by `0` or `1`.
+    Syntax,
+    /// A token was applied to something other than an array or an object.
+    NotContainer,
+    /// A token applied to an array is not an index, or is past the end of the array.
+    InvalidIndex,
+    /// A token applied to an object names a key it does not have, where one is needed.
+    MissingKey,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, at byte {} of the pointer", self.kind, self.offset)
+    }
+}
+
+impl fmt::Display for PointerErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax => f.write_str("invalid JSON pointer syntax"),
+            Self::NotContainer => f.write_str("pointer token applied to a non-container value"),
+            Self::InvalidIndex => f.write_str("invalid array index in pointer"),
+            Self::MissingKey => f.write_str("pointer token names a missing key"),
+        }
+    }
+}
+
+/// The reference tokens of a pointer, in order.
+pub(crate) struct Tokens<'p> {
+    rest: &'p str,
+    /// The byte offset of `rest` into the pointer.
+    offset: usize,
+}
+
+impl<'p> Tokens<'p> {
+    /// Checks the syntax of `ptr`.
+    pub(crate) const fn new(ptr: &'p str) -> Result<Self, PointerError> {
+        let bytes = ptr.as_bytes();
+        if !bytes.is_empty() && bytes[0] != b'/' {
+            return Err(PointerError {
+                kind: PointerErrorKind::Syntax,
+                offset: 0,
+            });
+        }
+        let mut idx = 0;
+        while idx < bytes.len() {
+            if bytes[idx] == b'~'
+                && (idx + 1 == bytes.len() || !matches!(bytes[idx + 1], b'0' | b'1'))
+            {
+                return Err(PointerError {
+                    kind: PointerErrorKind::Syntax,
+                    offset: idx,
+                });
+            }
+            idx += 1;
+        }
+        Ok(Self {
+            rest: ptr,
+            offset: 0,
+        })
+    }
+
+    pub(crate) const fn next(&mut self) -> Option<Token<'p>> {
+        let bytes = self.rest.as_bytes();
+        if bytes.is_empty() {
+            return None;
+        }
+        let mut end = 1;
+        while end < bytes.len() && bytes[end] != b'/' {
+            end += 1;
+        }
+        // Both splits are next to a `/`, so they are on character boundaries
+        let Some((token, rest)) = self.rest.split_at_checked(end) else {
+            unreachable!()
+        };
+        let Some((_, token)) = token.split_at_checked(1) else {
+            unreachable!()
+        };
+        let token = Token {
+            text: token,
+            #[cfg(feature = "alloc")]
+            offset: self.offset,
+        };
+        self.rest = rest;
+        self.offset += end;
+        Some(token)
+    }
+}
+
+/// A single reference token, still escaped.
+#[derive(Clone, Copy)]
+pub(crate) struct Token<'p> {
+    text: &'p str,
+    /// The byte offset into the pointer of the `/` before the token.
+    #[cfg(feature = "alloc")]
+    offset: usize,
+}
+
+impl Token<'_> {
+    /// An error about this token.
+    #[cfg(feature = "alloc")]
+    const fn error(self, kind: PointerErrorKind) -> PointerError {
+        PointerError {
+            kind,
+            offset: self.offset,
+        }
+    }
+
+    /// Whether the token refers to `key`.
+    pub(crate) const fn matches(self, key: &str) -> bool {
+        let (token, key) = (self.text.as_bytes(), key.as_bytes());
+        let (mut t, mut k) = (0, 0);
+        while t < token.len() && k < key.len() {
+            let b = if token[t] == b'~' {
+                t += 1;
+                if token[t] == b'0' { b'~' } else { b'/' }
+            } else {
+                token[t]
+            };
+            if b != key[k] {
+                return false;
+            }
+            t += 1;
+            k += 1;
+        }
+        t == token.len() && k == key.len()
+    }
+
+    /// The token as an index into an array of length `len`: a decimal number without leading
+    /// zeros, below `len`.
+    pub(crate) const fn index(self, len: usize) -> Option<usize> {
+        let bytes = self.text.as_bytes();
+        if bytes.is_empty() || (bytes[0] == b'0' && bytes.len() > 1) {
+            return None;
+        }
+        let mut index: usize = 0;
+        let mut idx = 0;
+        while idx < bytes.len() {
+            if !bytes[idx].is_ascii_digit() {
+                return None;
+            }
+            index = match index.checked_mul(10) {
+                Some(index) => match index.checked_add((bytes[idx] - b'0') as usize) {
+                    Some(index) => index,
+                    None => return None,
+                },
+                None => return None,
+            };
+            if index >= len {
+                return None;
+            }
+            idx += 1;
+        }
+        Some(index)
+    }
+
+    /// Whether the token is `-`, which refers to the position past the end of an array.
+    #[cfg(feature = "alloc")]
+    pub(crate) const fn is_past_end(self) -> bool {
+        matches!(self.text.as_bytes(), b"-")
+    }
+
+    #[cfg(feature = "alloc")]
+    fn unescape(self) -> String {
+        self.text.replace("~1", "/").replace("~0", "~")
+    }
+}
+
+impl Json<'_> {
+    /// Gets the value at a JSON Pointer, as defined by RFC 6901, in a `const` context. The empty
+    /// pointer gives `self`.
+    ///
+    /// Returns `None` if the pointer is not valid, or does not lead to a value. Keys containing
+    /// `/` or `~` are written with `~1` and `~0` in their place. The `-` token refers to the
+    /// position past the end of an array, so it never leads to a value.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const JSON: Json = const_json!({
+    ///     "servers": [{"host": "a"}],
+    ///     "a/b": 1,
+    ///     "m~n": 2,
+    ///     "~/": {"/~": 3},
+    ///     "a": {"b": 4}
+    /// });
+    ///
+    /// const HOST: &str = JSON.pointer("/servers/0/host").unwrap().str();
+    /// assert_eq!(HOST, "a");
+    ///
+    /// // Escaped keys are matched by their decoded form, never split at an escaped `/`
+    /// assert_eq!(JSON.pointer("/a~1b"), Some(&Json::Int(1)));
+    /// assert_eq!(JSON.pointer("/m~0n"), Some(&Json::Int(2)));
+    /// assert_eq!(JSON.pointer("/~0~1/~1~0"), Some(&Json::Int(3)));
+    /// assert_eq!(JSON.pointer("/a/b"), Some(&Json::Int(4)));
+    /// // `~01` is an escaped `~` followed by `1`, not an escaped `/`
+    /// assert_eq!(JSON.pointer("/m~01"), None);
+    ///
+    /// assert_eq!(JSON.pointer(""), Some(&JSON));
+    /// assert_eq!(JSON.pointer("/servers/-"), None);
+    /// assert_eq!(JSON.pointer("/a~2b"), None);
+    /// ```
+    pub const fn pointer(&self, ptr: &str) -> Option<&Self> {
+        let mut tokens = match Tokens::new(ptr) {
+            Ok(tokens) => tokens,
+            Err(_) => return None,
+        };
+        let mut target = self;
+        while let Some(token) = tokens.next() {
+            target = match target {
+                Self::Object(obj) => {
+                    let mut idx = 0;
+                    loop {
+                        if idx == obj.len() {
+                            return None;
+                        }
+                        if token.matches(obj[idx].0) {
+                            break &obj[idx].1;
+                        }
+                        idx += 1;
+                    }
+                }
+                Self::Array(arr) => match token.index(arr.len()) {
+                    Some(idx) => &arr[idx],
+                    None => return None,
+                },
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+}
+
+/// Follows `ptr` like [`Json::pointer`], but panics with the reason if it leads nowhere, which
+/// fails the build when called from a `const`.
+#[doc(hidden)]
+pub const fn resolve_pointer<'j, 'a>(json: &'j Json<'a>, ptr: &str) -> &'j Json<'a> {
+    if !ptr.is_empty() && ptr.as_bytes()[0] != b'/' {
+        fail(ptr, &[b"a pointer must be empty or start with '/'"]);
+    }
+    let mut tokens = match Tokens::new(ptr) {
+        Ok(tokens) => tokens,
+        Err(_) => fail(ptr, &[b"'~' must be followed by '0' or '1'"]),
+    };
+    let mut target = json;
+    while let Some(token) = tokens.next() {
+        let text = token.text.as_bytes();
+        target = match target {
+            Json::Object(obj) => {
+                let mut idx = 0;
+                loop {
+                    if idx == obj.len() {
+                        fail(ptr, &[b"key '", text, b"' not found"]);
+                    }
+                    if token.matches(obj[idx].0) {
+                        break &obj[idx].1;
+                    }
+                    idx += 1;
+                }
+            }
+            Json::Array(arr) => match token.index(arr.len()) {
+                Some(idx) => &arr[idx],
+                None if token.index(usize::MAX).is_some() => {
+                    let mut len = Message::new();
+                    len.push_number(arr.len());
+                    fail(
+                        ptr,
+                        &[
+                            b"index '",
+                            text,
+                            b"' is out of range for an array of length ",
+                            len.as_str().as_bytes(),
+                        ],
+                    )
+                }
+                None => fail(ptr, &[b"index '", text, b"' is not a valid array index"]),
+            },
+            _ => fail(
+                ptr,
+                &[
+                    b"cannot look up '",
+                    text,
+                    b"' in a ",
+                    target.type_name().as_bytes(),
+                ],
+            ),
+        };
+    }
+    target
+}
+
+/// Panics with the concatenation of `parts`, followed by the pointer.
+const fn fail(ptr: &str, parts: &[&[u8]]) -> ! {
+    let mut msg = Message::new();
+    let mut idx = 0;
+    while idx < parts.len() {
+        msg.push(parts[idx]);
+        idx += 1;
+    }
+    msg.push(b", in pointer '");
+    msg.push(ptr.as_bytes());
+    msg.push(b"'");
+    panic!("{}", msg.as_str())
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedJson {
+    /// Gets the value at a JSON Pointer, as defined by RFC 6901. The empty pointer gives `self`.
+    ///
+    /// Returns `None` if the pointer is not valid, or does not lead to a value. The `-` token
+    /// refers to the position past the end of an array, so it never leads to a value.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let json = OwnedJson::from(const_json!({"servers": [{"host": "a"}], "m~n": {"a/b": 1}}));
+    ///
+    /// assert_eq!(*json.get_pointer("/servers/0/host").unwrap(), const_json!("a"));
+    /// assert_eq!(*json.get_pointer("/m~0n/a~1b").unwrap(), const_json!(1));
+    /// assert_eq!(json.get_pointer("").unwrap(), &json);
+    /// assert_eq!(json.get_pointer("/servers/1"), None);
+    /// assert_eq!(json.get_pointer("/servers/-"), None);
+    /// assert_eq!(json.get_pointer("/servers/01"), None);
+    /// assert_eq!(json.get_pointer("servers"), None);
+    /// ```
+    pub fn get_pointer(&self, ptr: &str) -> Option<&Self> {
+        let mut tokens = Tokens::new(ptr).ok()?;
+        let mut target = self;
+        while let Some(token) = tokens.next() {
+            target = match target {
+                Self::Object(obj) => &obj.iter().find(|(k, _)| token.matches(k))?.1,
+                Self::Array(arr) => &arr[token.index(arr.len())?],
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Gets the value at a JSON Pointer mutably, following the same rules as
+    /// [`get_pointer`](Self::get_pointer).
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!({"stats": {"hits": [0, 41]}}));
+    ///
+    /// if let Some(OwnedJson::Int(hits)) = json.get_pointer_mut("/stats/hits/1") {
+    ///     *hits += 1;
+    /// }
+    /// assert_eq!(json, const_json!({"stats": {"hits": [0, 42]}}));
+    /// ```
+    pub fn get_pointer_mut(&mut self, ptr: &str) -> Option<&mut Self> {
+        let mut tokens = Tokens::new(ptr).ok()?;
+        let mut target = self;
+        while let Some(token) = tokens.next() {
+            target = match target {
+                Self::Object(obj) => &mut obj.iter_mut().find(|(k, _)| token.matches(k))?.1,
+                Self::Array(arr) => {
+                    let idx = token.index(arr.len())?;
+                    &mut arr[idx]
+                }
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Sets the value at a JSON Pointer, as defined by RFC 6901.
+    ///
+    /// The empty pointer replaces `self`. Otherwise, each token steps into an object by key or an
+    /// array by index, and the last one says where to put `value`:
+    /// - in an object, the entry with that key is replaced, or added at the end if there is none
+    /// - in an array, the element at that index is replaced, and the index one past the last
+    ///   element, or `-`, appends
+    ///
+    /// Missing objects along the way are created: a token naming a missing key, or `-` in an
+    /// array, adds an empty object there for the rest of the pointer to step into. Nothing else is
+    /// created, so stepping into a scalar, or past the end of an array, is an error. Errors are
+    /// found before anything is created, so on error `self` is left as it was.
+    ///
+    /// ```rust
+    /// use const_json::{OwnedJson, PointerErrorKind, const_json};
+    ///
+    /// let mut json = OwnedJson::from(const_json!({"servers": [{"host": "a"}], "name": "x"}));
+    ///
+    /// json.set_pointer("/servers/0/host", const_json!("b").into()).unwrap();
+    /// json.set_pointer("/servers/-", const_json!({"host": "c"}).into()).unwrap();
+    /// json.set_pointer("/limits/max", const_json!(10).into()).unwrap();
+    /// json.set_pointer("/a~1b", const_json!(true).into()).unwrap();
+    /// assert_eq!(json, const_json!({
+    ///     "servers": [{"host": "b"}, {"host": "c"}],
+    ///     "name": "x",
+    ///     "limits": {"max": 10},
+    ///     "a/b": true
+    /// }));
+    ///
+    /// let err = json.set_pointer("/name/first", OwnedJson::Null(())).unwrap_err();
+    /// assert_eq!((err.kind, err.offset), (PointerErrorKind::NotContainer, 5));
+    /// let err = json.set_pointer("/servers/5", OwnedJson::Null(())).unwrap_err();
+    /// assert_eq!((err.kind, err.offset), (PointerErrorKind::InvalidIndex, 8));
+    /// let err = json.set_pointer("servers", OwnedJson::Null(())).unwrap_err();
+    /// assert_eq!((err.kind, err.offset), (PointerErrorKind::Syntax, 0));
+    /// ```
+    pub fn set_pointer(&mut self, ptr: &str, value: OwnedJson) -> Result<(), PointerError> {
+        let mut tokens = Tokens::new(ptr)?;
+        let mut target = self;
+        while let Some(token) = tokens.next() {
+            target = match target {
+                Self::Object(obj) => {
+                    let idx = match obj.iter().position(|(k, _)| token.matches(k)) {
+                        Some(idx) => idx,
+                        None => {
+                            obj.push((token.unescape(), Self::Object(Vec::new())));
+                            obj.len() - 1
+                        }
+                    };
+                    &mut obj[idx].1
+                }
+                Self::Array(arr) => {
+                    let idx = match token.index(arr.len()) {
+                        Some(idx) => idx,
+                        None if token.is_past_end() || token.index(arr.len() + 1).is_some() => {
+                            arr.push(Self::Object(Vec::new()));
+                            arr.len() - 1
+                        }
+                        None => return Err(token.error(PointerErrorKind::InvalidIndex)),
+                    };
+                    &mut arr[idx]
+                }
+                _ => return Err(token.error(PointerErrorKind::NotContainer)),
+            };
+        }
+        *target = value;
+        Ok(())
+    }
+
+    /// Adds `value` at `ptr` the way a JSON Patch `add` does: in an object the entry with the last
+    /// key is replaced or added, and in an array `value` is inserted before the last index, or
+    /// appended for `-` or the index one past the end. Everything before the last token must
+    /// already exist.
+    pub(crate) fn add_pointer(&mut self, ptr: &str, value: OwnedJson) -> Result<(), PointerError> {
+        if Tokens::new(ptr)?.next().is_none() {
+            *self = value;
+            return Ok(());
+        }
+        let (parent, token) = self.parent_mut(ptr)?;
+        match parent {
+            Self::Object(obj) => match obj.iter().position(|(k, _)| token.matches(k)) {
+                Some(idx) => obj[idx].1 = value,
+                None => obj.push((token.unescape(), value)),
+            },
+            Self::Array(arr) => {
+                let idx = match token.index(arr.len() + 1) {
+                    Some(idx) => idx,
+                    None if token.is_past_end() => arr.len(),
+                    None => return Err(token.error(PointerErrorKind::InvalidIndex)),
+                };
+                arr.insert(idx, value);
+            }
+            _ => return Err(token.error(PointerErrorKind::NotContainer)),
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the value at `ptr`, which must exist. The empty pointer takes `self`,
+    /// leaving `null` behind.
+    pub(crate) fn remove_pointer(&mut self, ptr: &str) -> Result<OwnedJson, PointerError> {
+        if Tokens::new(ptr)?.next().is_none() {
+            return Ok(core::mem::replace(self, Self::Null(())));
+        }
+        let (parent, token) = self.parent_mut(ptr)?;
+        match parent {
+            Self::Object(obj) => match obj.iter().position(|(k, _)| token.matches(k)) {
+                Some(idx) => Ok(obj.remove(idx).1),
+                None => Err(token.error(PointerErrorKind::MissingKey)),
+            },
+            Self::Array(arr) => match token.index(arr.len()) {
+                Some(idx) => Ok(arr.remove(idx)),
+                None => Err(token.error(PointerErrorKind::InvalidIndex)),
+            },
+            _ => Err(token.error(PointerErrorKind::NotContainer)),
+        }
+    }
+
+    /// Gets the value at `ptr` mutably, like [`get_pointer_mut`](Self::get_pointer_mut), but says
+    /// why if there is none.
+    pub(crate) fn resolve_pointer_mut(&mut self, ptr: &str) -> Result<&mut Self, PointerError> {
+        let mut tokens = Tokens::new(ptr)?;
+        let mut target = self;
+        while let Some(token) = tokens.next() {
+            target = target.child_mut(token)?;
+        }
+        Ok(target)
+    }
+
+    /// Follows every token of the non-empty pointer `ptr` but the last, giving the value reached
+    /// along with the last token.
+    fn parent_mut<'p>(&mut self, ptr: &'p str) -> Result<(&mut Self, Token<'p>), PointerError> {
+        let mut tokens = Tokens::new(ptr)?;
+        let mut last = tokens.next().ok_or(PointerError {
+            kind: PointerErrorKind::Syntax,
+            offset: 0,
+        })?;
+        let mut target = self;
+        while let Some(token) = tokens.next() {
+            target = target.child_mut(last)?;
+            last = token;
+        }
+        Ok((target, last))
+    }
+
+    /// Steps into the value `token` refers to, which must exist.
+    fn child_mut(&mut self, token: Token<'_>) -> Result<&mut Self, PointerError> {
+        match self {
+            Self::Object(obj) => match obj.iter_mut().find(|(k, _)| token.matches(k)) {
+                Some((_, value)) => Ok(value),
+                None => Err(token.error(PointerErrorKind::MissingKey)),
+            },
+            Self::Array(arr) => match token.index(arr.len()) {
+                Some(idx) => Ok(&mut arr[idx]),
+                None => Err(token.error(PointerErrorKind::InvalidIndex)),
+            },
+            _ => Err(token.error(PointerErrorKind::NotContainer)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> Json<'a> {
+    /// Iterates over every value beneath the one at the JSON Pointer `prefix`, with the full
+    /// pointer to each. Values come in document order, each before the values inside it, and the
+    /// value at `prefix` itself is not included. Gives an empty iterator if `prefix` does not lead
+    /// to a value, following [`pointer`](Self::pointer).
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({
+    ///     "name": "app",
+    ///     "plugins": {"auth": {"users": ["ann"]}, "a/b": true}
+    /// });
+    ///
+    /// let plugins: Vec<(String, &Json)> = CONFIG.descendants("/plugins").collect();
+    /// assert_eq!(plugins, [
+    ///     ("/plugins/auth".to_string(), &const_json!({"users": ["ann"]})),
+    ///     ("/plugins/auth/users".to_string(), &const_json!(["ann"])),
+    ///     ("/plugins/auth/users/0".to_string(), &const_json!("ann")),
+    ///     ("/plugins/a~1b".to_string(), &const_json!(true)),
+    /// ]);
+    ///
+    /// assert_eq!(CONFIG.descendants("").count(), 6);
+    /// assert_eq!(CONFIG.descendants("/name").count(), 0);
+    /// assert_eq!(CONFIG.descendants("/missing").count(), 0);
+    /// ```
+    pub fn descendants<'j>(
+        &'j self,
+        prefix: &str,
+    ) -> impl Iterator<Item = (String, &'j Json<'a>)> + 'j {
+        let mut pending = Vec::new();
+        if let Some(root) = self.pointer(prefix) {
+            push_children(&mut pending, prefix, root);
+        }
+        core::iter::from_fn(move || {
+            let (ptr, json) = pending.pop()?;
+            push_children(&mut pending, &ptr, json);
+            Some((ptr, json))
+        })
+    }
+
+    /// Collects every value matching `pattern`, a JSON Pointer in which some tokens may be
+    /// wildcards:
+    /// - `*` matches any one key of an object or element of an array
+    /// - `**` matches any number of levels, including none, so `/**/id` finds every `id` key at
+    ///   any depth, including at the top level
+    ///
+    /// Other tokens match as in [`pointer`](Self::pointer), except that a key appearing more than
+    /// once in an object matches every time. When `**` is followed by more tokens, they are
+    /// matched from `**`'s own value and from every value beneath it, so `/**/a/b` finds each `b`
+    /// directly inside any `a`. Matches come in document order, each value at most once, however
+    /// many ways the pattern reaches it. An invalid pattern matches nothing. There is no way to
+    /// match a key that is literally `*` or `**` here; use [`pointer`](Self::pointer) for those.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const TREE: Json = const_json!({
+    ///     "name": "root",
+    ///     "children": [
+    ///         {"name": "a", "children": [{"name": "a1", "children": []}]},
+    ///         {"name": "b", "meta": {"name": "hidden"}}
+    ///     ]
+    /// });
+    ///
+    /// let names: Vec<&str> = TREE.query_all("/**/name").into_iter().map(Json::str).collect();
+    /// assert_eq!(names, ["root", "a", "a1", "b", "hidden"]);
+    ///
+    /// let top = TREE.query_all("/children/*/name");
+    /// assert_eq!(top, [&const_json!("a"), &const_json!("b")]);
+    ///
+    /// // Each `name` is found once, though `/**/**` can reach it in several ways
+    /// assert_eq!(TREE.query_all("/**/**/name").len(), 5);
+    /// assert_eq!(TREE.query_all("/**/children/0/name").len(), 2);
+    /// // Every value, the whole tree included
+    /// assert_eq!(TREE.query_all("/**").len(), 13);
+    /// assert!(TREE.query_all("name").is_empty());
+    /// ```
+    pub fn query_all<'j>(&'j self, pattern: &str) -> Vec<&'j Json<'a>> {
+        let mut found = Vec::new();
+        query(self, pattern, &mut |json| found.push(json));
+        found
+    }
+
+    /// Counts the values matching `pattern`, as [`query_all`](Self::query_all) finds them,
+    /// without collecting them.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({
+    ///     "servers": [{"host": "a", "port": 80}, {"host": "b"}, {"host": "c", "port": 81}],
+    ///     "backup": {"host": "d"}
+    /// });
+    ///
+    /// assert_eq!(CONFIG.query_count("/servers/*"), 3);
+    /// assert_eq!(CONFIG.query_count("/servers/*/port"), 2);
+    /// assert_eq!(CONFIG.query_count("/**/host"), 4);
+    /// assert_eq!(CONFIG.query_count("/backup"), 1);
+    /// assert_eq!(CONFIG.query_count("/servers/*/user"), 0);
+    /// assert_eq!(CONFIG.query_count("/servers/3"), 0);
+    /// ```
+    pub fn query_count(&self, pattern: &str) -> usize {
+        let mut count = 0;
+        query(self, pattern, &mut |_| count += 1);
+        count
+    }
+}
+
+/// Calls `found` with each value in `json` that matches `pattern`, in document order.
+///
+/// Each value is visited once, with the set of positions in the pattern from which the rest of
+/// it may still match, so a value reached in several ways through `**` is still found once. The
+/// walk keeps its own stack rather than recursing, so very deep values cannot overflow the call
+/// stack.
+#[cfg(feature = "alloc")]
+fn query<'j, 'a>(json: &'j Json<'a>, pattern: &str, found: &mut impl FnMut(&'j Json<'a>)) {
+    let Ok(mut tokens) = Tokens::new(pattern) else {
+        return;
+    };
+    let mut pattern = Vec::new();
+    while let Some(token) = tokens.next() {
+        pattern.push(token);
+    }
+    // Every pending value's positions are a range of this one buffer. Values are pushed after
+    // the ones below them on the stack, so their ranges come later, and everything past the
+    // range of the value just popped belongs to values that are finished.
+    let mut states = Vec::from([0]);
+    close_states(&pattern, &mut states, 0);
+    // Children are pushed in reverse, so that they are popped in document order
+    let mut pending = Vec::from([(json, 0..states.len())]);
+    while let Some((json, range)) = pending.pop() {
+        states.truncate(range.end);
+        if states[range.clone()].contains(&pattern.len()) {
+            found(json);
+        }
+        match *json {
+            Json::Array(arr) => {
+                for (idx, elem) in arr.iter().enumerate().rev() {
+                    let matches = |token: Token<'_>| token.index(arr.len()) == Some(idx);
+                    step(
+                        &pattern,
+                        &mut states,
+                        range.clone(),
+                        elem,
+                        matches,
+                        &mut pending,
+                    );
+                }
+            }
+            Json::Object(obj) => {
+                for (key, value) in obj.iter().rev() {
+                    let matches = |token: Token<'_>| token.matches(key);
+                    step(
+                        &pattern,
+                        &mut states,
+                        range.clone(),
+                        value,
+                        matches,
+                        &mut pending,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Works out the positions in `pattern` at which `child` is reached from a parent at
+/// `states[parent]`, where `matches` tells whether a token leads to `child`. If there are any,
+/// they are added to the end of `states` and `child` is pushed onto `pending` with their range.
+#[cfg(feature = "alloc")]
+fn step<'j, 'a>(
+    pattern: &[Token<'_>],
+    states: &mut Vec<usize>,
+    parent: Range<usize>,
+    child: &'j Json<'a>,
+    matches: impl Fn(Token<'_>) -> bool,
+    pending: &mut Vec<(&'j Json<'a>, Range<usize>)>,
+) {
+    let start = states.len();
+    for idx in parent {
+        let state = states[idx];
+        let Some(&token) = pattern.get(state) else {
+            continue;
+        };
+        match token.text {
+            "**" => states.push(state),
+            "*" => states.push(state + 1),
+            _ if matches(token) => states.push(state + 1),
+            _ => {}
+        }
+    }
+    if states.len() > start {
+        close_states(pattern, states, start);
+        pending.push((child, start..states.len()));
+    }
+}
+
+/// Adds the position after each `**` in `states[from..]`, since `**` may match no levels at all,
+/// then sorts them and removes duplicates.
+#[cfg(feature = "alloc")]
+fn close_states(pattern: &[Token<'_>], states: &mut Vec<usize>, from: usize) {
+    let mut idx = from;
+    while idx < states.len() {
+        if pattern
+            .get(states[idx])
+            .is_some_and(|token| token.text == "**")
+        {
+            states.push(states[idx] + 1);
+        }
+        idx += 1;
+    }
+    states[from..].sort_unstable();
+    let mut len = from;
+    for idx in from..states.len() {
+        if len == from || states[len - 1] != states[idx] {
+            states[len] = states[idx];
+            len += 1;
+        }
+    }
+    states.truncate(len);
+}
+
+/// Pushes the values inside `json` onto `pending` with their pointers, in reverse, so that they
+/// are popped in order.
+#[cfg(feature = "alloc")]
+fn push_children<'j, 'a>(pending: &mut Vec<(String, &'j Json<'a>)>, ptr: &str, json: &'j Json<'a>) {
+    match *json {
+        Json::Array(arr) => {
+            for (idx, elem) in arr.iter().enumerate().rev() {
+                pending.push((alloc::format!("{ptr}/{idx}"), elem));
+            }
+        }
+        Json::Object(obj) => {
+            for (key, value) in obj.iter().rev() {
+                let mut child = String::from(ptr);
+                push_token(&mut child, key);
+                pending.push((child, value));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Appends `key` to `ptr` as a reference token, with `~` and `/` escaped.
+#[cfg(feature = "alloc")]
+pub(crate) fn push_token(ptr: &mut String, key: &str) {
+    ptr.push('/');
+    for c in key.chars() {
+        match c {
+            '~' => ptr.push_str("~0"),
+            '/' => ptr.push_str("~1"),
+            c => ptr.push(c),
+        }
+    }
+}
@@ -0,0 +1,1245 @@
+//! Turning values back into JSON text.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::fmt::{self, Write};
+use core::slice;
+
+use crate::Json;
+#[cfg(feature = "alloc")]
+use crate::JsonError;
+use crate::escape::write_escaped;
+use crate::iter::Entries;
+
+/// Options for writing a [`Json`] value as JSON text.
+///
+/// Every serialization option lives here, and can be set either directly or through the builder
+/// methods of the same name. The [`Default`] writes compact JSON with object keys in insertion
+/// order, copies non-ASCII characters through as UTF-8, and writes non-finite floats as `null`.
+/// This is also what the [`Display`](fmt::Display) impl of [`Json`] uses, with `{:#}` setting
+/// [`pretty`](Self::pretty) to 2, except that it has no depth limit.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{Json, NanPolicy, Serializer, const_json};
+///
+/// const VALUE: Json = const_json!({"b": ["café", "😀"], "a": (f64::NAN)});
+/// assert_eq!(VALUE.to_string(), r#"{"b":["café","😀"],"a":null}"#);
+///
+/// let ascii = Serializer::new().ascii_only(true).sort_keys(true);
+/// assert_eq!(
+///     ascii.serialize(&VALUE).unwrap(),
+///     r#"{"a":null,"b":["caf\u00e9","\ud83d\ude00"]}"#,
+/// );
+///
+/// let pretty = Serializer::new().pretty(2).nan_policy(NanPolicy::Literal);
+/// assert_eq!(pretty.serialize(&VALUE).unwrap(), r#"{
+///   "b": [
+///     "café",
+///     "😀"
+///   ],
+///   "a": NaN
+/// }"#);
+///
+/// let strict = Serializer::new().nan_policy(NanPolicy::Error);
+/// assert!(strict.serialize(&VALUE).is_err());
+/// # }
+/// ```
+///
+/// The layout of pretty output can be tuned further, for example to match another formatter:
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{IndentStyle, Json, Serializer, const_json};
+///
+/// const VALUE: Json = const_json!({"point": [1, 2], "words": ["a long word", "another"], "none": {}});
+///
+/// let style = Serializer::new()
+///     .pretty(1)
+///     .indent_style(IndentStyle::Tabs)
+///     .inline_arrays(20)
+///     .space_in_empty(true);
+/// assert_eq!(
+///     style.serialize(&VALUE).unwrap(),
+///     "{\n\t\"point\": [1, 2],\n\t\"words\": [\n\t\t\"a long word\",\n\t\t\"another\"\n\t],\n\t\"none\": { }\n}",
+/// );
+/// assert_eq!(style.serialized_len(&VALUE).unwrap(), style.serialize(&VALUE).unwrap().len());
+/// # }
+/// ```
+///
+/// Serialization uses an explicit stack rather than recursion, so very deep values are reported
+/// as errors rather than overflowing the call stack:
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{Json, SerializeError, Serializer};
+///
+/// let mut deep = Json::Null(());
+/// for _ in 0..10_000 {
+///     deep = Json::Array(Box::leak(Box::new([deep])));
+/// }
+///
+/// let err = Serializer::new().serialize(&deep).unwrap_err();
+/// assert_eq!(err, SerializeError::DepthLimitExceeded);
+///
+/// let out = Serializer::new().max_depth(10_000).serialize(&deep).unwrap();
+/// assert_eq!(out, "[".repeat(10_000) + "null" + &"]".repeat(10_000));
+///
+/// // `Display` and the `to_*` shorthands have no limit
+/// assert_eq!(deep.to_string(), out);
+/// # }
+/// ```
+///
+/// Floats are written with the fewest digits that read back as the same `f64`, switching to
+/// exponent notation below `1e-6` and from `1e21`. They always include a decimal point or an
+/// exponent, so an integral float never comes out looking like a [`Json::Int`], and parsing the
+/// output gives back the same variant:
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{Json, const_json};
+///
+/// const FLOATS: Json = const_json!([0.1, 1e21, 1.0, 1e20, 123.456, (-0.000001), 1.5e-7, (f64::MAX)]);
+/// assert_eq!(
+///     FLOATS.to_string(),
+///     "[0.1,1e21,1.0,100000000000000000000.0,123.456,-0.000001,1.5e-7,1.7976931348623157e308]",
+/// );
+///
+/// use const_json::{OwnedJson, parse};
+///
+/// const FLOAT: Json = const_json!(1.0);
+/// const INT: Json = const_json!(1);
+/// assert_eq!(parse(&FLOAT.to_string()).unwrap(), OwnedJson::Float(1.0));
+/// assert_eq!(parse(&INT.to_string()).unwrap(), OwnedJson::Int(1));
+/// # }
+/// ```
+///
+/// JSON has no separate negative zero, and many readers treat `-0.0` as something other than
+/// `0.0`, so negative zero is written as `0.0`. The `const` writers such as
+/// [`Json::write_compact`] do the same:
+///
+/// ```rust
+/// use const_json::{Json, const_json};
+///
+/// const ZEROS: Json = const_json!([(-0.0), 0.0, (-0), (-1e-300 * 1e-300)]);
+/// const OUT: ([u8; 32], usize) = {
+///     let mut buf = [0; 32];
+///     let len = ZEROS.write_compact(&mut buf).unwrap();
+///     (buf, len)
+/// };
+/// assert_eq!(ZEROS.to_string(), "[0.0,0.0,0,0.0]");
+/// assert_eq!(&OUT.0[..OUT.1], ZEROS.to_string().as_bytes());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Serializer {
+    /// Put every array element and object entry on its own line, indented by this many spaces, or
+    /// tabs with [`indent_style`](Self::indent_style), per level of nesting. `None` writes
+    /// everything on one line, with no whitespace.
+    pub pretty: Option<usize>,
+    /// Whether [`pretty`](Self::pretty) indents with spaces or tabs.
+    pub indent_style: IndentStyle,
+    /// When pretty printing, keep an array on one line, as in `[1, 2, 3]`, if none of its
+    /// elements are arrays or objects and that line is at most this many bytes long. The width
+    /// counts the array alone, not its indentation or key. `None` puts every element on its own
+    /// line.
+    pub inline_arrays: Option<usize>,
+    /// When pretty printing, write an array or object on one line, as in `[1, {"a": 2}]`, if that
+    /// line is at most this many bytes long. Unlike [`inline_arrays`](Self::inline_arrays), the
+    /// width counts from the start of the line, taking in the indentation, one byte per indent
+    /// character, and the key of an object entry. Containers that do not fit are expanded as
+    /// usual, and their contents considered the same way. `None` expands every container that
+    /// `inline_arrays` does not keep on one line.
+    pub max_width: Option<usize>,
+    /// When pretty printing, write empty arrays and objects as `[ ]` and `{ }` rather than `[]`
+    /// and `{}`.
+    pub space_in_empty: bool,
+    /// End the output with a single `\n` after the value, as `jq` and many formatters do, so that
+    /// written files are POSIX text files.
+    pub trailing_newline: bool,
+    /// Write object entries sorted by key, comparing bytes, rather than in insertion order. Entries
+    /// with the same key keep their relative order. Without the `alloc` feature there is nowhere to
+    /// sort them, so each object takes time quadratic in its number of entries.
+    pub sort_keys: bool,
+    /// Escape every non-ASCII character in strings as `\uXXXX`, using a UTF-16 surrogate pair for
+    /// characters outside the Basic Multilingual Plane, so the output is pure ASCII. Hex digits
+    /// are lowercase.
+    ///
+    /// A `str` is always valid UTF-8, so it can never hold a lone surrogate, and every surrogate
+    /// written is half of a correctly ordered pair that parses back to the same character.
+    pub ascii_only: bool,
+    /// Escape every `/` in strings as `\/`, so that the output can be embedded in an HTML
+    /// `<script>` element without a `</script>` inside a string ending it early. JSON allows `/`
+    /// either way.
+    pub escape_slashes: bool,
+    /// What to do with floats that JSON cannot represent.
+    pub nan_policy: NanPolicy,
+    /// Write every float in plain decimal notation, such as `1000000000000000000000.0` rather
+    /// than `1e21`, for readers that reject exponents. The digits are still the fewest that read
+    /// back as the same `f64`, padded with zeros, so extreme magnitudes come out long: [`f64::MAX`]
+    /// takes 311 bytes, and the smallest subnormal float 326.
+    pub no_exponent: bool,
+    /// Fail on an object that uses a key more than once, rather than writing every entry. With the
+    /// `alloc` feature, the whole value is checked before anything is written, as
+    /// [`Json::find_duplicate_key`] does, and the error is a [`SerializeError::DuplicateKey`]
+    /// telling which key it was and where. Without it, each object is checked as it is reached,
+    /// and the error is a [`SerializeError::DuplicateEntry`].
+    pub error_on_duplicate_keys: bool,
+    /// How many arrays and objects may be nested inside each other before serialization fails
+    /// with [`SerializeError::DepthLimitExceeded`]. Defaults to [`MAX_DEPTH`](Self::MAX_DEPTH).
+    ///
+    /// Serialization does not recurse, so deep values cannot overflow the stack. Instead, the
+    /// containers being written are tracked in a buffer, which lives on the heap when the `alloc`
+    /// feature is enabled. Without it, the buffer is a fixed array of `MAX_DEPTH` entries, and
+    /// larger limits are treated as `MAX_DEPTH`.
+    pub max_depth: usize,
+}
+
+impl Default for Serializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The character a pretty-printing [`Serializer`] indents with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// Indent with spaces.
+    #[default]
+    Spaces,
+    /// Indent with tabs, [`pretty`](Serializer::pretty) of them per level.
+    Tabs,
+}
+
+/// How a [`Serializer`] handles NaN and infinite floats, which JSON has no syntax for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Write `null` in their place.
+    #[default]
+    Null,
+    /// Write `NaN`, `Infinity` or `-Infinity`. This is not valid JSON, but is valid JSON5.
+    Literal,
+    /// Fail with [`SerializeError::NonFinite`].
+    Error,
+}
+
+/// An error encountered while serializing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SerializeError {
+    /// A NaN or infinite float was found, and the [`NanPolicy`] was [`NanPolicy::Error`].
+    NonFinite,
+    /// Arrays and objects were nested deeper than [`Serializer::max_depth`].
+    DepthLimitExceeded,
+    /// An object used a key more than once, and [`Serializer::error_on_duplicate_keys`] was set.
+    /// This needs the `alloc` feature, to keep the key and where it was.
+    #[cfg(feature = "alloc")]
+    DuplicateKey {
+        /// The repeated key.
+        key: String,
+        /// A JSON Pointer to the object holding it.
+        pointer: String,
+    },
+    /// Like [`DuplicateKey`](Self::DuplicateKey), when there is nowhere to keep the key because
+    /// the `alloc` feature is disabled. It is never returned with `alloc`.
+    DuplicateEntry {
+        /// How many arrays and objects the object is nested in.
+        depth: usize,
+        /// The index of the first entry whose key was used by an earlier entry.
+        index: usize,
+    },
+    /// The underlying writer failed.
+    Fmt,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFinite => f.write_str("non-finite float cannot be serialized"),
+            Self::DepthLimitExceeded => f.write_str("nesting depth limit exceeded"),
+            #[cfg(feature = "alloc")]
+            Self::DuplicateKey { key, pointer } => {
+                write!(f, "duplicate key '{key}' in object at '{pointer}'")
+            }
+            Self::DuplicateEntry { depth, index } => write!(
+                f,
+                "entry {index} of an object at depth {depth} repeats an earlier key"
+            ),
+            Self::Fmt => f.write_str("failed to write output"),
+        }
+    }
+}
+
+impl From<fmt::Error> for SerializeError {
+    fn from(_: fmt::Error) -> Self {
+        Self::Fmt
+    }
+}
+
+impl Serializer {
+    /// The default [`max_depth`](Self::max_depth).
+    pub const MAX_DEPTH: usize = 128;
+
+    /// Creates a serializer with the default options.
+    pub const fn new() -> Self {
+        Self {
+            pretty: None,
+            indent_style: IndentStyle::Spaces,
+            inline_arrays: None,
+            max_width: None,
+            space_in_empty: false,
+            trailing_newline: false,
+            sort_keys: false,
+            ascii_only: false,
+            escape_slashes: false,
+            nan_policy: NanPolicy::Null,
+            no_exponent: false,
+            error_on_duplicate_keys: false,
+            max_depth: Self::MAX_DEPTH,
+        }
+    }
+
+    /// The options of the [`Display`](fmt::Display) impl of [`Json`] and the `to_*` shorthands:
+    /// the defaults, with no depth limit. The stack grows as needed with `alloc`, and without it
+    /// is limited to [`MAX_DEPTH`](Self::MAX_DEPTH) anyway.
+    const fn unlimited() -> Self {
+        Self::new().max_depth(usize::MAX)
+    }
+
+    /// Sets [`pretty`](Self::pretty) to the given indent.
+    pub const fn pretty(self, indent: usize) -> Self {
+        Self {
+            pretty: Some(indent),
+            ..self
+        }
+    }
+
+    /// Sets [`indent_style`](Self::indent_style).
+    pub const fn indent_style(self, indent_style: IndentStyle) -> Self {
+        Self {
+            indent_style,
+            ..self
+        }
+    }
+
+    /// Sets [`inline_arrays`](Self::inline_arrays) to the given width.
+    pub const fn inline_arrays(self, width: usize) -> Self {
+        Self {
+            inline_arrays: Some(width),
+            ..self
+        }
+    }
+
+    /// Sets [`max_width`](Self::max_width) to the given width.
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "alloc")] {
+    /// use const_json::{Json, Serializer, const_json};
+    ///
+    /// const VALUE: Json = const_json!({
+    ///     "name": "app",
+    ///     "ports": [80, 443],
+    ///     "deps": {"serde": {"version": "1.0", "features": ["derive"]}, "log": "0.4"}
+    /// });
+    ///
+    /// let wrapped = Serializer::new().pretty(2).max_width(60);
+    /// assert_eq!(wrapped.serialize(&VALUE).unwrap(), r#"{
+    ///   "name": "app",
+    ///   "ports": [80, 443],
+    ///   "deps": {
+    ///     "serde": {"version": "1.0", "features": ["derive"]},
+    ///     "log": "0.4"
+    ///   }
+    /// }"#);
+    ///
+    /// let wide = Serializer::new().pretty(2).max_width(120);
+    /// assert_eq!(
+    ///     wide.serialize(&VALUE).unwrap(),
+    ///     r#"{"name": "app", "ports": [80, 443], "deps": {"serde": {"version": "1.0", "features": ["derive"]}, "log": "0.4"}}"#,
+    /// );
+    /// assert_eq!(wide.serialized_len(&VALUE).unwrap(), wide.serialize(&VALUE).unwrap().len());
+    /// # }
+    /// ```
+    pub const fn max_width(self, width: usize) -> Self {
+        Self {
+            max_width: Some(width),
+            ..self
+        }
+    }
+
+    /// Sets [`space_in_empty`](Self::space_in_empty).
+    pub const fn space_in_empty(self, space_in_empty: bool) -> Self {
+        Self {
+            space_in_empty,
+            ..self
+        }
+    }
+
+    /// Sets [`trailing_newline`](Self::trailing_newline).
+    pub const fn trailing_newline(self, trailing_newline: bool) -> Self {
+        Self {
+            trailing_newline,
+            ..self
+        }
+    }
+
+    /// Sets [`sort_keys`](Self::sort_keys).
+    pub const fn sort_keys(self, sort_keys: bool) -> Self {
+        Self { sort_keys, ..self }
+    }
+
+    /// Sets [`ascii_only`](Self::ascii_only).
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "alloc")] {
+    /// use const_json::{Json, Serializer, const_json, parse};
+    ///
+    /// const ASTRAL: Json = const_json!(["😀", "𝄞", "\u{10ffff}", "\u{ffff}", "é"]);
+    ///
+    /// let out = Serializer::new().ascii_only(true).serialize(&ASTRAL).unwrap();
+    /// assert_eq!(out, r#"["\ud83d\ude00","\ud834\udd1e","\udbff\udfff","\uffff","\u00e9"]"#);
+    /// assert_eq!(parse(&out).unwrap(), ASTRAL);
+    /// # }
+    /// ```
+    pub const fn ascii_only(self, ascii_only: bool) -> Self {
+        Self { ascii_only, ..self }
+    }
+
+    /// Sets [`escape_slashes`](Self::escape_slashes).
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "alloc")] {
+    /// use const_json::{Json, Serializer, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"html": "<script>alert(1)</script>"});
+    /// assert_eq!(VALUE.to_string(), r#"{"html":"<script>alert(1)</script>"}"#);
+    ///
+    /// let escaped = Serializer::new().escape_slashes(true);
+    /// assert_eq!(
+    ///     escaped.serialize(&VALUE).unwrap(),
+    ///     r#"{"html":"<script>alert(1)<\/script>"}"#,
+    /// );
+    /// assert_eq!(escaped.serialized_len(&VALUE).unwrap(), VALUE.to_string().len() + 1);
+    /// # }
+    /// ```
+    pub const fn escape_slashes(self, escape_slashes: bool) -> Self {
+        Self {
+            escape_slashes,
+            ..self
+        }
+    }
+
+    /// Sets [`nan_policy`](Self::nan_policy).
+    pub const fn nan_policy(self, nan_policy: NanPolicy) -> Self {
+        Self { nan_policy, ..self }
+    }
+
+    /// Sets [`no_exponent`](Self::no_exponent).
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "alloc")] {
+    /// use const_json::{Json, Serializer, const_json, parse};
+    ///
+    /// const VALUES: Json = const_json!([1e21, 1.5e-7, 2.5]);
+    /// assert_eq!(VALUES.to_string(), "[1e21,1.5e-7,2.5]");
+    ///
+    /// let plain = Serializer::new().no_exponent(true);
+    /// let out = plain.serialize(&VALUES).unwrap();
+    /// assert_eq!(out, "[1000000000000000000000.0,0.00000015,2.5]");
+    /// assert_eq!(parse(&out).unwrap(), VALUES);
+    ///
+    /// let max = plain.serialize(&Json::Float(f64::MAX)).unwrap();
+    /// assert_eq!(max.len(), 311);
+    /// assert!(max.starts_with("17976931348623157000") && max.ends_with("000.0"));
+    /// assert_eq!(plain.serialize(&Json::Float(5e-324)).unwrap().len(), 326);
+    /// # }
+    /// ```
+    pub const fn no_exponent(self, no_exponent: bool) -> Self {
+        Self {
+            no_exponent,
+            ..self
+        }
+    }
+
+    /// Sets [`error_on_duplicate_keys`](Self::error_on_duplicate_keys).
+    ///
+    /// ```rust
+    /// use const_json::{Json, SerializeError, Serializer, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"user": {"id": 1, "id": 2}});
+    /// assert_eq!(VALUE.to_string(), r#"{"user":{"id":1,"id":2}}"#);
+    ///
+    /// let strict = Serializer::new().error_on_duplicate_keys(true);
+    /// let err = strict.serialize_to(&VALUE, &mut String::new()).unwrap_err();
+    /// # #[cfg(feature = "alloc")] {
+    /// assert_eq!(
+    ///     err,
+    ///     SerializeError::DuplicateKey { key: "id".into(), pointer: "/user".into() },
+    /// );
+    /// assert_eq!(err.to_string(), "duplicate key 'id' in object at '/user'");
+    /// # }
+    ///
+    /// // Without `alloc`, the error gives the entry's place instead
+    /// # #[cfg(not(feature = "alloc"))] {
+    /// assert_eq!(err, SerializeError::DuplicateEntry { depth: 1, index: 1 });
+    /// assert_eq!(err.to_string(), "entry 1 of an object at depth 1 repeats an earlier key");
+    /// # }
+    /// ```
+    pub const fn error_on_duplicate_keys(self, error_on_duplicate_keys: bool) -> Self {
+        Self {
+            error_on_duplicate_keys,
+            ..self
+        }
+    }
+
+    /// Sets [`max_depth`](Self::max_depth).
+    pub const fn max_depth(self, max_depth: usize) -> Self {
+        Self { max_depth, ..self }
+    }
+
+    /// Writes `value` to `w`.
+    pub fn serialize_to<W: Write>(
+        &self,
+        value: &Json<'_>,
+        w: &mut W,
+    ) -> Result<(), SerializeError> {
+        #[cfg(feature = "alloc")]
+        if self.error_on_duplicate_keys
+            && let Some(dup) = value.find_duplicate_key()
+        {
+            return Err(SerializeError::DuplicateKey {
+                key: dup.key.into(),
+                pointer: dup.pointer,
+            });
+        }
+        let mut stack = Stack::new();
+        self.value(value, w, &mut stack, 0)?;
+
+        while let Some(frame) = stack.last_mut() {
+            let (elem, first) = match frame {
+                Frame::Array { elems, first } => match elems.next() {
+                    Some(elem) => (elem, core::mem::replace(first, false)),
+                    None => {
+                        stack.pop();
+                        self.newline(w, stack.len())?;
+                        w.write_char(']')?;
+                        continue;
+                    }
+                },
+                Frame::Object { entries, first } => {
+                    let Some((key, val)) = entries.next() else {
+                        stack.pop();
+                        self.newline(w, stack.len())?;
+                        w.write_char('}')?;
+                        continue;
+                    };
+                    if !core::mem::replace(first, false) {
+                        w.write_char(',')?;
+                    }
+                    self.newline(w, stack.len())?;
+                    self.string(key, w)?;
+                    w.write_str(if self.pretty.is_some() { ": " } else { ":" })?;
+                    let column = match self.max_width {
+                        Some(_) => self.column(stack.len()) + self.string_len(key) + 2,
+                        None => 0,
+                    };
+                    self.value(val, w, &mut stack, column)?;
+                    continue;
+                }
+            };
+            if !first {
+                w.write_char(',')?;
+            }
+            self.newline(w, stack.len())?;
+            let column = self.column(stack.len());
+            self.value(elem, w, &mut stack, column)?;
+        }
+        if self.trailing_newline {
+            w.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Writes `value` to a new [`String`].
+    #[cfg(feature = "alloc")]
+    pub fn serialize(&self, value: &Json<'_>) -> Result<String, SerializeError> {
+        let mut out = String::new();
+        self.serialize_to(value, &mut out)?;
+        Ok(out)
+    }
+
+    /// Writes a scalar in full, or opens a container and pushes it onto `stack` so that its
+    /// contents are written by the loop in [`serialize_to`](Self::serialize_to). `column` is how
+    /// many bytes are already on the line.
+    fn value<'j, 'a, W: Write>(
+        &self,
+        value: &'j Json<'a>,
+        w: &mut W,
+        stack: &mut Stack<Frame<'j, 'a>>,
+        column: usize,
+    ) -> Result<(), SerializeError> {
+        #[cfg(not(feature = "alloc"))]
+        if let Json::Object(entries) = *value {
+            self.check_duplicates(entries, stack.len())?;
+        }
+        let frame = match *value {
+            Json::Null(()) => return Ok(w.write_str("null")?),
+            Json::Bool(b) => return Ok(write!(w, "{b}")?),
+            Json::Float(f) if !f.is_finite() => {
+                let literal = match self.nan_policy {
+                    NanPolicy::Null => "null",
+                    NanPolicy::Literal if f.is_nan() => "NaN",
+                    NanPolicy::Literal if f > 0.0 => "Infinity",
+                    NanPolicy::Literal => "-Infinity",
+                    NanPolicy::Error => return Err(SerializeError::NonFinite),
+                };
+                return Ok(w.write_str(literal)?);
+            }
+            Json::Float(f) => return Ok(write_float(w, f, self.no_exponent)?),
+            Json::Int(i) => return Ok(write!(w, "{i}")?),
+            Json::Str(s) => return Ok(self.string(s, w)?),
+            Json::Array([]) | Json::Object([]) => {
+                let space = self.space_in_empty && self.pretty.is_some();
+                let empty = match (value, space) {
+                    (Json::Array(_), false) => "[]",
+                    (Json::Array(_), true) => "[ ]",
+                    (_, false) => "{}",
+                    (_, true) => "{ }",
+                };
+                return Ok(w.write_str(empty)?);
+            }
+            Json::Array(elems) if self.fits_inline(elems)? => {
+                w.write_char('[')?;
+                for (idx, elem) in elems.iter().enumerate() {
+                    if idx > 0 {
+                        w.write_str(", ")?;
+                    }
+                    self.value(elem, w, stack, 0)?;
+                }
+                return Ok(w.write_char(']')?);
+            }
+            Json::Array(_) | Json::Object(_) if self.fits_width(value, stack.len(), column)? => {
+                return self.inline(value, w, stack.len());
+            }
+            Json::Array(elems) => Frame::Array {
+                elems: elems.iter(),
+                first: true,
+            },
+            Json::Object(entries) => Frame::Object {
+                entries: Entries::new(entries, self.sort_keys),
+                first: true,
+            },
+        };
+        if stack.len() >= Stack::<Frame>::limit(self.max_depth) {
+            return Err(SerializeError::DepthLimitExceeded);
+        }
+        w.write_char(if let Frame::Array { .. } = frame {
+            '['
+        } else {
+            '{'
+        })?;
+        stack.push(frame);
+        Ok(())
+    }
+
+    /// Whether `elems` should be written on one line, following
+    /// [`inline_arrays`](Self::inline_arrays).
+    fn fits_inline(&self, elems: &[Json<'_>]) -> Result<bool, SerializeError> {
+        let (Some(_), Some(width)) = (self.pretty, self.inline_arrays) else {
+            return Ok(false);
+        };
+        if elems
+            .iter()
+            .any(|elem| matches!(elem, Json::Array(_) | Json::Object(_)))
+        {
+            return Ok(false);
+        }
+        // The brackets, and a comma and a space between each pair of elements
+        let mut counter = Counter(elems.len() * 2);
+        // Scalars never touch the stack
+        let mut stack = Stack::new();
+        for elem in elems {
+            self.value(elem, &mut counter, &mut stack, 0)?;
+        }
+        Ok(counter.0 <= width)
+    }
+
+    /// Whether `value`, at `depth` and starting `column` bytes into its line, should be written
+    /// on one line, following [`max_width`](Self::max_width).
+    fn fits_width(
+        &self,
+        value: &Json<'_>,
+        depth: usize,
+        column: usize,
+    ) -> Result<bool, SerializeError> {
+        let (Some(_), Some(width)) = (self.pretty, self.max_width) else {
+            return Ok(false);
+        };
+        let Some(budget) = width.checked_sub(column) else {
+            return Ok(false);
+        };
+        match self.inline(value, &mut Budget(budget), depth) {
+            Ok(()) => Ok(true),
+            Err(SerializeError::Fmt) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes `value` on one line, as in `[1, {"a": 2}]`, when it is nested in `depth`
+    /// containers. When measuring with a [`Budget`], this stops at the first byte past it, so
+    /// checking a container that does not fit costs at most the width, however large it is.
+    fn inline<W: Write>(
+        &self,
+        value: &Json<'_>,
+        w: &mut W,
+        depth: usize,
+    ) -> Result<(), SerializeError> {
+        let mut stack = Stack::new();
+        let mut next = Some(value);
+        loop {
+            if let Some(value) = next {
+                let frame = match *value {
+                    Json::Array([_, ..]) | Json::Object([_, ..])
+                        if depth + stack.len() >= Stack::<Frame>::limit(self.max_depth) =>
+                    {
+                        return Err(SerializeError::DepthLimitExceeded);
+                    }
+                    Json::Array(elems @ [_, ..]) => {
+                        w.write_char('[')?;
+                        Some(Frame::Array {
+                            elems: elems.iter(),
+                            first: true,
+                        })
+                    }
+                    Json::Object(entries @ [_, ..]) => {
+                        #[cfg(not(feature = "alloc"))]
+                        self.check_duplicates(entries, depth + stack.len())?;
+                        w.write_char('{')?;
+                        Some(Frame::Object {
+                            entries: Entries::new(entries, self.sort_keys),
+                            first: true,
+                        })
+                    }
+                    // Scalars and empty containers never touch the stack
+                    _ => {
+                        self.value(value, w, &mut stack, 0)?;
+                        None
+                    }
+                };
+                if let Some(frame) = frame {
+                    stack.push(frame);
+                }
+            }
+            let Some(frame) = stack.last_mut() else {
+                return Ok(());
+            };
+            next = match frame {
+                Frame::Array { elems, first } => match elems.next() {
+                    Some(elem) => {
+                        if !core::mem::replace(first, false) {
+                            w.write_str(", ")?;
+                        }
+                        Some(elem)
+                    }
+                    None => {
+                        w.write_char(']')?;
+                        None
+                    }
+                },
+                Frame::Object { entries, first } => match entries.next() {
+                    Some((key, val)) => {
+                        if !core::mem::replace(first, false) {
+                            w.write_str(", ")?;
+                        }
+                        self.string(key, w)?;
+                        w.write_str(": ")?;
+                        Some(val)
+                    }
+                    None => {
+                        w.write_char('}')?;
+                        None
+                    }
+                },
+            };
+            if next.is_none() {
+                stack.pop();
+            }
+        }
+    }
+
+    /// Fails if `entries`, nested in `depth` containers, should be rejected under
+    /// [`error_on_duplicate_keys`](Self::error_on_duplicate_keys). With `alloc`, the whole value
+    /// is checked up front by [`serialize_to`](Self::serialize_to) instead.
+    #[cfg(not(feature = "alloc"))]
+    fn check_duplicates(
+        &self,
+        entries: &[(&str, Json<'_>)],
+        depth: usize,
+    ) -> Result<(), SerializeError> {
+        if !self.error_on_duplicate_keys {
+            return Ok(());
+        }
+        match Json::duplicate_entry(entries) {
+            Some(index) => Err(SerializeError::DuplicateEntry { depth, index }),
+            None => Ok(()),
+        }
+    }
+
+    /// How many bytes of indentation start a line at the given depth.
+    fn column(&self, depth: usize) -> usize {
+        self.pretty.unwrap_or(0) * depth
+    }
+
+    /// The length of `s` once quoted and escaped.
+    fn string_len(&self, s: &str) -> usize {
+        let mut counter = Counter(0);
+        // Writing to a `Counter` cannot fail
+        let _ = self.string(s, &mut counter);
+        counter.0
+    }
+
+    /// Starts a new line at the given depth, if pretty printing.
+    fn newline<W: Write>(&self, w: &mut W, depth: usize) -> fmt::Result {
+        if let Some(indent) = self.pretty {
+            w.write_char('\n')?;
+            let c = match self.indent_style {
+                IndentStyle::Spaces => ' ',
+                IndentStyle::Tabs => '\t',
+            };
+            for _ in 0..indent * depth {
+                w.write_char(c)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn string<W: Write>(&self, s: &str, w: &mut W) -> fmt::Result {
+        w.write_char('"')?;
+        write_escaped(w, s, self.ascii_only, self.escape_slashes)?;
+        w.write_char('"')
+    }
+}
+
+/// A container that is partway through being written.
+enum Frame<'j, 'a> {
+    Array {
+        elems: slice::Iter<'j, Json<'a>>,
+        first: bool,
+    },
+    Object {
+        entries: Entries<'j, 'a>,
+        first: bool,
+    },
+}
+
+/// The containers that are partway through being written, innermost last.
+pub(crate) struct Stack<T> {
+    #[cfg(feature = "alloc")]
+    frames: Vec<T>,
+    #[cfg(not(feature = "alloc"))]
+    frames: [Option<T>; Serializer::MAX_DEPTH],
+    #[cfg(not(feature = "alloc"))]
+    len: usize,
+}
+
+impl<T> Stack<T> {
+    #[cfg(feature = "alloc")]
+    pub(crate) fn new() -> Self {
+        Self { frames: Vec::new() }
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) fn new() -> Self {
+        Self {
+            frames: [const { None }; Serializer::MAX_DEPTH],
+            len: 0,
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the stack is at its capacity, which it only has without `alloc`.
+    pub(crate) fn is_full(&self) -> bool {
+        self.len() >= Self::limit(usize::MAX)
+    }
+
+    /// How many frames the stack may hold with the given `max_depth`, which is at most its
+    /// capacity.
+    fn limit(max_depth: usize) -> usize {
+        #[cfg(not(feature = "alloc"))]
+        let max_depth = max_depth.min(Serializer::MAX_DEPTH);
+        max_depth
+    }
+
+    /// Pushes a frame. The caller must have checked the stack is below its
+    /// [`limit`](Self::limit).
+    pub(crate) fn push(&mut self, frame: T) {
+        #[cfg(feature = "alloc")]
+        self.frames.push(frame);
+        #[cfg(not(feature = "alloc"))]
+        {
+            self.frames[self.len] = Some(frame);
+            self.len += 1;
+        }
+    }
+
+    pub(crate) fn pop(&mut self) {
+        #[cfg(feature = "alloc")]
+        self.frames.pop();
+        #[cfg(not(feature = "alloc"))]
+        {
+            self.len -= 1;
+            self.frames[self.len] = None;
+        }
+    }
+
+    pub(crate) fn last_mut(&mut self) -> Option<&mut T> {
+        #[cfg(feature = "alloc")]
+        return self.frames.last_mut();
+        #[cfg(not(feature = "alloc"))]
+        return self.frames[..self.len].last_mut()?.as_mut();
+    }
+}
+
+/// Writes a finite float using the fewest significant digits that parse back to the same value.
+///
+/// The digits are written out in full for magnitudes from `1e-6` up to `1e21`, and in exponent
+/// notation outside that range, like JavaScript's `Number.prototype.toString`, unless
+/// `no_exponent` is set. Either way, the output always has a decimal point or an exponent, so it
+/// never looks like an integer.
+fn write_float<W: Write>(w: &mut W, f: f64, no_exponent: bool) -> fmt::Result {
+    // Negative zero compares equal to zero, so this writes it as `0.0`
+    let f = if f == 0.0 { 0.0 } else { f };
+    // `{:e}` already picks the shortest round-tripping digits, as `d.ddde-x`
+    let mut buf = Buffer {
+        bytes: [0; 32],
+        len: 0,
+    };
+    write!(buf, "{f:e}")?;
+    let text = buf.as_str();
+
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(text) => ("-", text),
+        None => ("", text),
+    };
+    let (mantissa, exp) = text.split_once('e').ok_or(fmt::Error)?;
+    let exp: i32 = exp.parse().map_err(|_| fmt::Error)?;
+    let (lead, rest) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    w.write_str(sign)?;
+    let plain = no_exponent || (-7 < exp && exp < 21);
+    if !plain {
+        w.write_str(mantissa)?;
+        return write!(w, "e{exp}");
+    }
+    if exp < 0 {
+        w.write_str("0.")?;
+        for _ in 0..-exp - 1 {
+            w.write_char('0')?;
+        }
+        w.write_str(lead)?;
+        return w.write_str(rest);
+    }
+
+    let int_len = exp as usize;
+    w.write_str(lead)?;
+    if rest.len() > int_len {
+        w.write_str(&rest[..int_len])?;
+        w.write_char('.')?;
+        w.write_str(&rest[int_len..])
+    } else {
+        w.write_str(rest)?;
+        for _ in rest.len()..int_len {
+            w.write_char('0')?;
+        }
+        w.write_str(".0")
+    }
+}
+
+impl Serializer {
+    /// Turns on pretty printing with an indent of 2 if `{:#}` was used, for `Display` impls.
+    fn pretty_if_alternate(self, f: &fmt::Formatter<'_>) -> Self {
+        if f.alternate() { self.pretty(2) } else { self }
+    }
+}
+
+/// A fixed-size buffer for formatting short strings without allocating.
+struct Buffer {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl Buffer {
+    fn as_str(&self) -> &str {
+        // Only ever written to with whole `str`s
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// A writer that counts down how many more bytes fit on a line, failing once they run out.
+struct Budget(usize);
+
+impl Write for Budget {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = self.0.checked_sub(s.len()).ok_or(fmt::Error)?;
+        Ok(())
+    }
+}
+
+/// A writer that only counts the bytes written to it.
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+impl Serializer {
+    /// Computes the exact length in bytes of what [`serialize`](Self::serialize) would write for
+    /// `value`, without writing it anywhere.
+    pub fn serialized_len(&self, value: &Json<'_>) -> Result<usize, SerializeError> {
+        let mut counter = Counter(0);
+        self.serialize_to(value, &mut counter)?;
+        Ok(counter.0)
+    }
+}
+
+impl Json<'_> {
+    /// Writes the compact serialization of `self` into a fixed-capacity string, without
+    /// allocating.
+    ///
+    /// Fails with [`SerializeError::Fmt`] if the output does not fit in `N` bytes.
+    /// [`serialized_len`](Self::serialized_len) gives the capacity needed. Other [`Serializer`]
+    /// options can be used the same way, by passing the string to
+    /// [`serialize_to`](Serializer::serialize_to).
+    ///
+    /// ```rust
+    /// use const_json::{Json, SerializeError, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"id": 7, "tags": ["a", "b"]});
+    /// let out = VALUE.to_heapless::<32>().unwrap();
+    /// assert_eq!(out.as_str(), r#"{"id":7,"tags":["a","b"]}"#);
+    ///
+    /// assert_eq!(VALUE.to_heapless::<8>(), Err(SerializeError::Fmt));
+    /// ```
+    #[cfg(feature = "heapless")]
+    pub fn to_heapless<const N: usize>(&self) -> Result<heapless::String<N>, SerializeError> {
+        let mut out = heapless::String::new();
+        Serializer::new().serialize_to(self, &mut out)?;
+        Ok(out)
+    }
+
+    /// Writes `self` as pretty-printed JSON, indented by `indent` spaces per level of nesting.
+    ///
+    /// This is shorthand for [`Serializer::pretty`]; see [`Serializer`] for the other options.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"a": [1, {}]});
+    /// assert_eq!(VALUE.to_string_pretty(4), "{\n    \"a\": [\n        1,\n        {}\n    ]\n}");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        let mut out = String::new();
+        // With no depth limit, writing to a `String` cannot fail
+        let _ = Serializer::unlimited()
+            .pretty(indent)
+            .serialize_to(self, &mut out);
+        out
+    }
+
+    /// Like [`to_string_pretty`](Self::to_string_pretty), but ending with a newline, as a file
+    /// should. This is shorthand for [`Serializer::trailing_newline`].
+    ///
+    /// ```rust
+    /// use const_json::{Json, Serializer, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"a": [1, {}]});
+    /// assert_eq!(VALUE.to_string_pretty_nl(2), "{\n  \"a\": [\n    1,\n    {}\n  ]\n}\n");
+    /// assert_eq!(VALUE.to_string_pretty_nl(2), VALUE.to_string_pretty(2) + "\n");
+    ///
+    /// let compact = Serializer::new().trailing_newline(true);
+    /// assert_eq!(compact.serialize(&VALUE).unwrap(), "{\"a\":[1,{}]}\n");
+    /// assert_eq!(compact.serialized_len(&VALUE).unwrap(), 13);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_string_pretty_nl(&self, indent: usize) -> String {
+        let mut out = String::new();
+        // With no depth limit, writing to a `String` cannot fail
+        let _ = Serializer::unlimited()
+            .pretty(indent)
+            .trailing_newline(true)
+            .serialize_to(self, &mut out);
+        out
+    }
+
+    /// Writes each element of an array as compact JSON on its own line, joined by `\n`, in the
+    /// JSON Lines (NDJSON) format for streams of records. Nested arrays and objects stay on their
+    /// element's line. There is no newline after the last element, so add one when writing a file.
+    ///
+    /// Fails with [`JsonError::WrongType`] if `self` is not a [`Json::Array`].
+    ///
+    /// ```rust
+    /// use const_json::{Json, JsonError, const_json};
+    ///
+    /// const EVENTS: Json = const_json!([{"id": 1, "tags": ["a"]}, {"id": 2, "tags": []}, null]);
+    /// assert_eq!(
+    ///     EVENTS.to_ndjson().unwrap(),
+    ///     "{\"id\":1,\"tags\":[\"a\"]}\n{\"id\":2,\"tags\":[]}\nnull",
+    /// );
+    /// assert_eq!(const_json!([]).to_ndjson().unwrap(), "");
+    ///
+    /// let err = const_json!({"id": 1}).to_ndjson().unwrap_err();
+    /// assert_eq!(err, JsonError::WrongType { expected: "array", found: "object" });
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_ndjson(&self) -> Result<String, JsonError<'static>> {
+        let Self::Array(elems) = *self else {
+            return Err(JsonError::WrongType {
+                expected: "array",
+                found: self.type_name(),
+            });
+        };
+        let mut out = String::new();
+        for (idx, elem) in elems.iter().enumerate() {
+            if idx > 0 {
+                out.push('\n');
+            }
+            // With no depth limit, writing to a `String` cannot fail
+            let _ = Serializer::unlimited().serialize_to(elem, &mut out);
+        }
+        Ok(out)
+    }
+
+    /// Writes the compact serialization of `self` as UTF-8 bytes, for byte-oriented sinks.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"name": "café", "list": [1, 2.5, null]});
+    /// assert_eq!(VALUE.to_vec(), VALUE.to_string().into_bytes());
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut out = String::new();
+        // With no depth limit, writing to a `String` cannot fail
+        let _ = Serializer::unlimited().serialize_to(self, &mut out);
+        out.into_bytes()
+    }
+
+    /// Writes `self` as pretty-printed JSON in UTF-8 bytes, like
+    /// [`to_string_pretty`](Self::to_string_pretty).
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"a": [1, {}]});
+    /// assert_eq!(VALUE.to_vec_pretty(2), VALUE.to_string_pretty(2).into_bytes());
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_vec_pretty(&self, indent: usize) -> Vec<u8> {
+        self.to_string_pretty(indent).into_bytes()
+    }
+}
+
+impl Json<'_> {
+    /// Displays `self` as compact JSON that is pure ASCII, whatever the sink, with non-ASCII
+    /// characters escaped as `\uXXXX`. This is shorthand for [`Serializer::ascii_only`]. Like the
+    /// [`Display`](fmt::Display) impl of [`Json`], `{:#}` pretty prints it instead.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"name": "café ☕"});
+    /// let line = format!("config: {}", VALUE.display_ascii());
+    /// assert_eq!(line, r#"config: {"name":"caf\u00e9 \u2615"}"#);
+    /// ```
+    pub fn display_ascii(&self) -> impl fmt::Display + '_ {
+        struct Ascii<'j, 'a>(&'j Json<'a>);
+
+        impl fmt::Display for Ascii<'_, '_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Serializer::unlimited()
+                    .ascii_only(true)
+                    .pretty_if_alternate(f)
+                    .serialize_to(self.0, f)
+                    .map_err(|_| fmt::Error)
+            }
+        }
+
+        Ascii(self)
+    }
+}
+
+/// Writes `value` as pretty-printed JSON, indented by `indent` spaces per level of nesting, like
+/// `serde_json::to_string_pretty`.
+///
+/// This is the same as [`Json::to_string_pretty`], and as a [`Serializer`] with only
+/// [`pretty`](Serializer::pretty) set. Build a [`Serializer`] to combine it with other options.
+///
+/// ```rust
+/// use const_json::{Json, Serializer, const_json, to_string_pretty};
+///
+/// const VALUE: Json = const_json!({"a": [1, {}]});
+/// assert_eq!(to_string_pretty(&VALUE, 2), VALUE.to_string_pretty(2));
+/// assert_eq!(to_string_pretty(&VALUE, 2), Serializer::new().pretty(2).serialize(&VALUE).unwrap());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn to_string_pretty(value: &Json<'_>, indent: usize) -> String {
+    value.to_string_pretty(indent)
+}
+
+/// Writes compact JSON with `{}`, and JSON pretty printed with an indent of 2 with `{:#}`, using
+/// the default [`Serializer`]. Either way the output goes straight to the formatter, with nothing
+/// buffered.
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use const_json::{Json, const_json};
+///
+/// const VALUE: Json = const_json!({"name": "app", "ports": [80, 443], "tls": {"on": true}});
+///
+/// assert_eq!(format!("{VALUE}"), r#"{"name":"app","ports":[80,443],"tls":{"on":true}}"#);
+/// assert_eq!(format!("{VALUE:#}"), r#"{
+///   "name": "app",
+///   "ports": [
+///     80,
+///     443
+///   ],
+///   "tls": {
+///     "on": true
+///   }
+/// }"#);
+/// assert_eq!(format!("{VALUE:#}"), VALUE.to_string_pretty(2));
+/// # }
+/// ```
+impl fmt::Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Serializer::unlimited()
+            .pretty_if_alternate(f)
+            .serialize_to(self, f)
+            .map_err(|_| fmt::Error)
+    }
+}
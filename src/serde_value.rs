@@ -0,0 +1,75 @@
+//! Comparisons with [`serde_json::Value`], for testing code that bridges the two.
+
+use serde_json::{Number, Value};
+
+use crate::Json;
+use crate::keys::sorted_duplicate_entry;
+
+/// Compares structurally, with the same rules as [`Json::json_eq`] except that objects are
+/// compared regardless of order, since a [`serde_json::Map`]'s order depends on its features.
+/// Numbers compare numerically and exactly, so `Int(1)` equals a `serde_json` `1.0`, and an object
+/// with duplicate keys equals no `Value`.
+///
+/// ```rust
+/// use const_json::{Json, const_json};
+/// use serde_json::json;
+///
+/// const JSON: Json = const_json!({"name": "example", "sizes": [1, 2.5], "empty": null});
+///
+/// assert_eq!(JSON, json!({"empty": null, "sizes": [1.0, 2.5], "name": "example"}));
+/// assert_ne!(JSON, json!({"name": "example", "sizes": [1, 2.5]}));
+///
+/// // A repeated key is never matched twice
+/// assert_ne!(const_json!({"a": 1, "a": 1}), json!({"a": 1, "b": 2}));
+///
+/// // Integers above `i64::MAX` can only be stored as floats
+/// assert_eq!(Json::Float(2f64.powi(63)), json!(1u64 << 63));
+/// assert_ne!(Json::Float(u64::MAX as f64), json!(u64::MAX));
+/// ```
+impl PartialEq<Value> for Json<'_> {
+    fn eq(&self, other: &Value) -> bool {
+        match (*self, other) {
+            (Self::Null(()), Value::Null) => true,
+            (Self::Bool(l), Value::Bool(r)) => l == *r,
+            (Self::Int(_) | Self::Float(_), Value::Number(r)) => number_eq(self, r),
+            (Self::Str(l), Value::String(r)) => l == r,
+            (Self::Array(l), Value::Array(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(l, r)| l == r)
+            }
+            (Self::Object(l), Value::Object(r)) => {
+                // Keys in `r` are unique, so once `l` has no repeats either, matching the same
+                // number of entries by key pairs them all up
+                l.len() == r.len()
+                    && sorted_duplicate_entry(l).is_none()
+                    && l.iter().all(|(k, v)| r.get(*k).is_some_and(|r| v == r))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<Json<'_>> for Value {
+    fn eq(&self, other: &Json<'_>) -> bool {
+        other == self
+    }
+}
+
+fn number_eq(json: &Json<'_>, number: &Number) -> bool {
+    if let Some(r) = number.as_i64() {
+        match *json {
+            Json::Int(l) => l == r,
+            _ => Json::Int(r).json_eq(json),
+        }
+    } else if let Some(r) = number.as_u64() {
+        // Above `i64::MAX`, so only a float can hold it. Every float in this range is an integer,
+        // and 2^64 is the first one past `u64::MAX`
+        match *json {
+            Json::Float(l) => l < 18_446_744_073_709_551_616.0 && l as u64 == r,
+            _ => false,
+        }
+    } else {
+        number
+            .as_f64()
+            .is_some_and(|r| Json::Float(r).json_eq(json))
+    }
+}
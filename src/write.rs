@@ -0,0 +1,422 @@
+//! Serialization into fixed buffers, in a `const` context.
+//!
+//! The output is byte for byte what the default [`Serializer`] writes, set to
+//! [`pretty`](Serializer::pretty) or not, so text built at compile time matches text built at
+//! runtime.
+
+use crate::escape::escape_byte;
+use crate::float::format_exp;
+use crate::{Json, Serializer};
+
+/// Writes bytes into a buffer, still counting them once it is full.
+struct Writer<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> Writer<'b> {
+    const fn new(buf: &'b mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    const fn byte(&mut self, b: u8) {
+        if self.len < self.buf.len() {
+            self.buf[self.len] = b;
+        }
+        self.len += 1;
+    }
+
+    const fn bytes(&mut self, bytes: &[u8]) {
+        let mut idx = 0;
+        while idx < bytes.len() {
+            self.byte(bytes[idx]);
+            idx += 1;
+        }
+    }
+
+    const fn string(&mut self, s: &str) {
+        let bytes = s.as_bytes();
+        self.byte(b'"');
+        let mut idx = 0;
+        while idx < bytes.len() {
+            let (escape, len) = escape_byte(bytes[idx]);
+            let mut e = 0;
+            while e < len {
+                self.byte(escape[e]);
+                e += 1;
+            }
+            idx += 1;
+        }
+        self.byte(b'"');
+    }
+
+    const fn int(&mut self, i: i64) {
+        if i < 0 {
+            self.byte(b'-');
+        }
+        let mut magnitude = i.unsigned_abs();
+        let mut digits = [0; 20];
+        let mut count = 0;
+        loop {
+            digits[count] = b'0' + (magnitude % 10) as u8;
+            count += 1;
+            magnitude /= 10;
+            if magnitude == 0 {
+                break;
+            }
+        }
+        while count > 0 {
+            count -= 1;
+            self.byte(digits[count]);
+        }
+    }
+
+    /// Writes a float the way the runtime serializer does, from its `{:e}` form.
+    const fn float(&mut self, f: f64) {
+        if !f.is_finite() {
+            return self.bytes(b"null");
+        }
+        // Like the runtime serializer, writing negative zero as `0.0`
+        let f = if f == 0.0 { 0.0 } else { f };
+        let (text, len) = format_exp(f);
+        let mut idx = 0;
+        if text[0] == b'-' {
+            self.byte(b'-');
+            idx = 1;
+        }
+        // The digits, without the point, and the exponent of the first
+        let mut digits = [0; 17];
+        let mut count = 0;
+        while text[idx] != b'e' {
+            if text[idx] != b'.' {
+                digits[count] = text[idx];
+                count += 1;
+            }
+            idx += 1;
+        }
+        let exp_at = idx + 1;
+        let negative = text[exp_at] == b'-';
+        idx = if negative { exp_at + 1 } else { exp_at };
+        let mut exp: i32 = 0;
+        while idx < len {
+            exp = exp * 10 + (text[idx] - b'0') as i32;
+            idx += 1;
+        }
+        if negative {
+            exp = -exp;
+        }
+
+        if !(-7 < exp && exp < 21) {
+            idx = if text[0] == b'-' { 1 } else { 0 };
+            while idx < len {
+                self.byte(text[idx]);
+                idx += 1;
+            }
+        } else if exp < 0 {
+            self.bytes(b"0.");
+            let mut zeros = -exp - 1;
+            while zeros > 0 {
+                self.byte(b'0');
+                zeros -= 1;
+            }
+            self.digits(&digits, 0, count);
+        } else {
+            let int_len = exp as usize + 1;
+            if count > int_len {
+                self.digits(&digits, 0, int_len);
+                self.byte(b'.');
+                self.digits(&digits, int_len, count);
+            } else {
+                self.digits(&digits, 0, count);
+                let mut zeros = int_len - count;
+                while zeros > 0 {
+                    self.byte(b'0');
+                    zeros -= 1;
+                }
+                self.bytes(b".0");
+            }
+        }
+    }
+
+    const fn digits(&mut self, digits: &[u8; 17], mut start: usize, end: usize) {
+        while start < end {
+            self.byte(digits[start]);
+            start += 1;
+        }
+    }
+
+    /// Starts a new line at the given depth, if pretty printing.
+    const fn newline(&mut self, pretty: Option<usize>, depth: usize) {
+        if let Some(indent) = pretty {
+            self.byte(b'\n');
+            let mut spaces = indent * depth;
+            while spaces > 0 {
+                self.byte(b' ');
+                spaces -= 1;
+            }
+        }
+    }
+
+    /// The number of bytes written, if they all fit.
+    const fn finish(&self) -> Option<usize> {
+        if self.len <= self.buf.len() {
+            Some(self.len)
+        } else {
+            None
+        }
+    }
+}
+
+/// A container that is partway through being written.
+#[derive(Clone, Copy)]
+enum Frame<'j, 'a> {
+    Array(&'j [Json<'a>]),
+    Object(&'j [(&'a str, Json<'a>)]),
+}
+
+/// Writes `json`, pretty printed if `pretty` is set, using an explicit stack rather than
+/// recursion.
+const fn write_json(json: &Json<'_>, w: &mut Writer<'_>, pretty: Option<usize>) {
+    let mut frames = [Frame::Array(&[]); Serializer::MAX_DEPTH];
+    // How many elements or entries of each frame have been written
+    let mut written = [0; Serializer::MAX_DEPTH];
+    let mut depth = 0;
+    let mut next = Some(json);
+    loop {
+        if let Some(value) = next {
+            next = None;
+            let frame = match *value {
+                Json::Null(()) => {
+                    w.bytes(b"null");
+                    None
+                }
+                Json::Bool(true) => {
+                    w.bytes(b"true");
+                    None
+                }
+                Json::Bool(false) => {
+                    w.bytes(b"false");
+                    None
+                }
+                Json::Int(i) => {
+                    w.int(i);
+                    None
+                }
+                Json::Float(f) => {
+                    w.float(f);
+                    None
+                }
+                Json::Str(s) => {
+                    w.string(s);
+                    None
+                }
+                Json::Array([]) => {
+                    w.bytes(b"[]");
+                    None
+                }
+                Json::Object([]) => {
+                    w.bytes(b"{}");
+                    None
+                }
+                Json::Array(elems) => {
+                    w.byte(b'[');
+                    Some(Frame::Array(elems))
+                }
+                Json::Object(entries) => {
+                    w.byte(b'{');
+                    Some(Frame::Object(entries))
+                }
+            };
+            if let Some(frame) = frame {
+                if depth == Serializer::MAX_DEPTH {
+                    panic!("nesting depth limit exceeded");
+                }
+                frames[depth] = frame;
+                written[depth] = 0;
+                depth += 1;
+            }
+        }
+
+        if depth == 0 {
+            return;
+        }
+        let top = depth - 1;
+        let (len, close) = match frames[top] {
+            Frame::Array(elems) => (elems.len(), b']'),
+            Frame::Object(entries) => (entries.len(), b'}'),
+        };
+        let idx = written[top];
+        if idx == len {
+            depth -= 1;
+            w.newline(pretty, depth);
+            w.byte(close);
+            continue;
+        }
+        if idx > 0 {
+            w.byte(b',');
+        }
+        w.newline(pretty, depth);
+        written[top] += 1;
+        next = match frames[top] {
+            Frame::Array(elems) => Some(&elems[idx]),
+            Frame::Object(entries) => {
+                let (key, ref value) = entries[idx];
+                w.string(key);
+                w.byte(b':');
+                if pretty.is_some() {
+                    w.byte(b' ');
+                }
+                Some(value)
+            }
+        };
+    }
+}
+
+impl Json<'_> {
+    /// Writes the compact serialization of `self` into the start of `buf`, in a `const` context,
+    /// exactly as its [`Display`](core::fmt::Display) impl would. Returns the number of
+    /// bytes written, or `None` if they do not fit, in which case the contents of `buf` are
+    /// unspecified. [`serialized_len`](Self::serialized_len) gives the size needed.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"id": 7, "ratio": 0.5, "tags": ["a\nb"]});
+    /// const OUT: ([u8; 64], usize) = {
+    ///     let mut buf = [0; 64];
+    ///     let len = VALUE.write_compact(&mut buf).unwrap();
+    ///     (buf, len)
+    /// };
+    /// let out = std::str::from_utf8(&OUT.0[..OUT.1]).unwrap();
+    /// assert_eq!(out, r#"{"id":7,"ratio":0.5,"tags":["a\nb"]}"#);
+    /// assert_eq!(out, VALUE.to_string());
+    ///
+    /// assert_eq!(VALUE.write_compact(&mut [0; 8]), None);
+    /// ```
+    ///
+    /// Strings are escaped the same way too, with the short escapes `\b`, `\f`, `\n`, `\r` and
+    /// `\t` for the control characters that have one, and `\u00XX` for the rest:
+    ///
+    /// ```rust
+    /// use const_json::{Json, Serializer, const_json, escape_into};
+    ///
+    /// const CONTROLS: Json = const_json!("\u{8}\u{c}\n\r\t\u{1}");
+    /// const OUT: ([u8; 32], usize) = {
+    ///     let mut buf = [0; 32];
+    ///     let len = CONTROLS.write_compact(&mut buf).unwrap();
+    ///     (buf, len)
+    /// };
+    /// let out = std::str::from_utf8(&OUT.0[..OUT.1]).unwrap();
+    /// assert_eq!(out, r#""\b\f\n\r\t\u0001""#);
+    /// # #[cfg(feature = "alloc")]
+    /// assert_eq!(out, CONTROLS.to_string());
+    /// # #[cfg(feature = "alloc")]
+    /// assert_eq!(out, Serializer::new().pretty(2).serialize(&CONTROLS).unwrap());
+    ///
+    /// let mut buf = [0; 32];
+    /// let len = escape_into(CONTROLS.str(), &mut buf).unwrap();
+    /// assert_eq!(&buf[..len], &OUT.0[1..OUT.1 - 1]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is nested deeper than [`Serializer::MAX_DEPTH`].
+    pub const fn write_compact(&self, buf: &mut [u8]) -> Option<usize> {
+        let mut w = Writer::new(buf);
+        write_json(self, &mut w, None);
+        w.finish()
+    }
+
+    /// Writes `self` pretty printed with `indent` spaces per level into the start of `buf`, in a
+    /// `const` context, exactly as [`to_string_pretty`](Self::to_string_pretty) would. Returns the
+    /// number of bytes written, or `None` if they do not fit, in which case the contents of `buf`
+    /// are unspecified. [`pretty_len`](Self::pretty_len) gives the size needed.
+    ///
+    /// Together they can embed a readable rendering of a document as a `&'static str`, with no
+    /// work at runtime:
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const CONFIG: Json = const_json!({"name": "app", "ports": [80, 443], "tls": {}});
+    ///
+    /// const LEN: usize = CONFIG.pretty_len(2);
+    /// const BYTES: [u8; LEN] = {
+    ///     let mut buf = [0; LEN];
+    ///     CONFIG.write_pretty(&mut buf, 2).unwrap();
+    ///     buf
+    /// };
+    /// const PRETTY: &str = match core::str::from_utf8(&BYTES) {
+    ///     Ok(s) => s,
+    ///     Err(_) => panic!("not UTF-8"),
+    /// };
+    ///
+    /// assert_eq!(PRETTY, r#"{
+    ///   "name": "app",
+    ///   "ports": [
+    ///     80,
+    ///     443
+    ///   ],
+    ///   "tls": {}
+    /// }"#);
+    /// # #[cfg(feature = "alloc")]
+    /// assert_eq!(PRETTY, CONFIG.to_string_pretty(2));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is nested deeper than [`Serializer::MAX_DEPTH`].
+    pub const fn write_pretty(&self, buf: &mut [u8], indent: usize) -> Option<usize> {
+        let mut w = Writer::new(buf);
+        write_json(self, &mut w, Some(indent));
+        w.finish()
+    }
+
+    /// Computes the exact length in bytes of the compact serialization of `self`, in a `const`
+    /// context.
+    ///
+    /// ```rust
+    /// use const_json::{Json, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"name": "café", "list": [1, 2.5, null]});
+    /// const LEN: usize = VALUE.serialized_len();
+    /// assert_eq!(LEN, VALUE.to_string().len());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is nested deeper than [`Serializer::MAX_DEPTH`].
+    pub const fn serialized_len(&self) -> usize {
+        let mut w = Writer::new(&mut []);
+        write_json(self, &mut w, None);
+        w.len
+    }
+
+    /// Computes the exact length in bytes of [`to_string_pretty`](Self::to_string_pretty) with
+    /// the same `indent`, including newlines and indentation, in a `const` context, so a buffer
+    /// can be allocated up front, or sized for [`write_pretty`](Self::write_pretty).
+    ///
+    /// ```rust
+    /// use const_json::{Json, Serializer, const_json};
+    ///
+    /// const VALUE: Json = const_json!({"name": "café", "list": [1, 2.5, [], {"a": null}]});
+    /// # #[cfg(feature = "alloc")] {
+    /// assert_eq!(VALUE.pretty_len(4), VALUE.to_string_pretty(4).len());
+    ///
+    /// let mut out = String::with_capacity(VALUE.pretty_len(2));
+    /// let capacity = out.capacity();
+    /// Serializer::new().pretty(2).serialize_to(&VALUE, &mut out).unwrap();
+    /// // No reallocation was needed
+    /// assert_eq!(out.capacity(), capacity);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is nested deeper than [`Serializer::MAX_DEPTH`].
+    pub const fn pretty_len(&self, indent: usize) -> usize {
+        let mut w = Writer::new(&mut []);
+        write_json(self, &mut w, Some(indent));
+        w.len
+    }
+}